@@ -2,17 +2,54 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use egui::{CursorIcon, Label, RichText, Ui};
-use egui_dock::DockState;
+use egui_dock::{DockState, NodeIndex, SurfaceIndex, TabIndex};
 use rfd::FileDialog;
 
+use self::bookmarks::SharedBookmarkStore;
+use self::command_palette::{CommandPalette, PaletteAction, PaletteItem, PaletteMode};
+use self::global_search::GlobalSearchWindow;
+use self::key_bindings::{Command, KeyBindings, KeyBindingsWindow};
 use self::log_view::LogView;
-
+use self::pattern_comparison::PatternComparisonWindow;
+use self::search_library::SharedSearchLibrary;
+use self::text_encoding::TextEncoding;
+
+pub mod bookmarks;
+pub mod bookmarks_tab;
+pub mod command_palette;
+pub mod field_path;
+pub mod fields;
+pub mod fields_tab;
+pub mod filter_chain_tab;
 pub mod filtered_log_entries_tab;
+pub mod global_search;
+pub mod go_to_dialog;
+pub mod jq;
+pub mod jq_tab;
+pub mod key_bindings;
+pub mod level_filter;
+pub mod log_analysis;
 pub mod log_entries_tab;
 pub mod log_entries_table;
 pub mod log_entry_context_tab;
 pub mod log_file_reader;
 pub mod log_view;
+pub mod notes;
+pub mod notes_tab;
+pub mod parse_diagnostics_tab;
+pub mod pattern_comparison;
+pub mod patterns;
+pub mod patterns_tab;
+pub mod query;
+pub mod repeats;
+pub mod search_job;
+pub mod search_library;
+pub mod stats_tab;
+pub mod text_encoding;
+pub mod time_histogram;
+pub mod time_range;
+pub mod time_scroll_bar;
+pub mod timestamp;
 
 struct LogViewTabViewer;
 
@@ -39,6 +76,24 @@ pub struct TemplateApp {
     tree: DockState<LogView>,
 
     recent_files: Vec<PathBuf>,
+
+    key_bindings: KeyBindings,
+
+    search_library: SharedSearchLibrary,
+
+    bookmarks: SharedBookmarkStore,
+
+    #[serde(skip)]
+    command_palette: Option<CommandPalette>,
+
+    #[serde(skip)]
+    key_bindings_window: Option<KeyBindingsWindow>,
+
+    #[serde(skip)]
+    global_search: Option<GlobalSearchWindow>,
+
+    #[serde(skip)]
+    pattern_comparison: Option<PatternComparisonWindow>,
 }
 
 impl Default for TemplateApp {
@@ -46,6 +101,13 @@ impl Default for TemplateApp {
         Self {
             tree: DockState::new(vec![]),
             recent_files: vec![],
+            key_bindings: Default::default(),
+            search_library: Default::default(),
+            bookmarks: Default::default(),
+            command_palette: None,
+            key_bindings_window: None,
+            global_search: None,
+            pattern_comparison: None,
         }
     }
 }
@@ -83,13 +145,208 @@ impl TemplateApp {
         };
 
         if let Some(ref file_path) = file_to_open {
-            self.tree.push_to_first_leaf(LogView::open(file_path).ok()?);
+            let log_view = LogView::open(
+                file_path,
+                self.search_library.clone(),
+                self.bookmarks.clone(),
+            )
+            .ok()?;
+            self.tree.push_to_first_leaf(log_view);
             self.add_recent_file(file_path);
         }
 
         Some(())
     }
 
+    /// The view commands go to: the focused one, or the first one if none has been focused yet.
+    fn active_log_view(&mut self) -> Option<&mut LogView> {
+        if self.tree.find_active_focused().is_some() {
+            return self
+                .tree
+                .find_active_focused()
+                .map(|(_, log_view)| log_view);
+        }
+        self.tree
+            .iter_all_tabs_mut()
+            .next()
+            .map(|(_, log_view)| log_view)
+    }
+
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::ShowPalette => {
+                self.command_palette = Some(CommandPalette::new(PaletteMode::Commands))
+            }
+            Command::OpenFile => {
+                self.open_file(None);
+            }
+            Command::OpenRecent => {
+                self.command_palette = Some(CommandPalette::new(PaletteMode::RecentFiles))
+            }
+            Command::AddColumn => {
+                self.command_palette = Some(CommandPalette::new(PaletteMode::AddColumn))
+            }
+            Command::RemoveColumn => {
+                self.command_palette = Some(CommandPalette::new(PaletteMode::RemoveColumn))
+            }
+            Command::SearchAllFiles => {
+                self.global_search
+                    .get_or_insert_with(GlobalSearchWindow::new);
+            }
+            Command::ComparePatterns => {
+                self.pattern_comparison
+                    .get_or_insert_with(PatternComparisonWindow::new);
+            }
+            Command::ResetUi => ctx.memory_mut(|mem| *mem = Default::default()),
+            Command::KeyboardShortcuts => self.key_bindings_window = Some(KeyBindingsWindow::new()),
+            _ => {
+                if let Some(log_view) = self.active_log_view() {
+                    log_view.run_command(command);
+                }
+            }
+        }
+    }
+
+    /// Items listed by the command palette in the given mode.
+    fn palette_items(&mut self, ctx: &egui::Context, mode: PaletteMode) -> Vec<PaletteItem> {
+        match mode {
+            PaletteMode::Commands => Command::ALL
+                .iter()
+                .map(|&command| PaletteItem {
+                    label: command.name().to_string(),
+                    detail: self
+                        .key_bindings
+                        .shortcut_text(ctx, command)
+                        .unwrap_or_default(),
+                    action: PaletteAction::Run(command),
+                })
+                .collect(),
+            PaletteMode::RecentFiles => self
+                .recent_files
+                .iter()
+                .rev()
+                .map(|path| PaletteItem {
+                    label: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    detail: path
+                        .parent()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    action: PaletteAction::OpenFile(path.clone()),
+                })
+                .collect(),
+            PaletteMode::AddColumn => self
+                .active_log_view()
+                .map(|log_view| log_view.available_columns())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|column| PaletteItem {
+                    label: column.clone(),
+                    detail: String::new(),
+                    action: PaletteAction::AddColumn(column),
+                })
+                .collect(),
+            PaletteMode::RemoveColumn => self
+                .active_log_view()
+                .map(|log_view| log_view.displayed_columns().to_vec())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|column| PaletteItem {
+                    label: column.clone(),
+                    detail: String::new(),
+                    action: PaletteAction::RemoveColumn(column),
+                })
+                .collect(),
+        }
+    }
+
+    fn command_palette_ui(&mut self, ctx: &egui::Context) {
+        let Some(mode) = self.command_palette.as_ref().map(|palette| palette.mode()) else {
+            return;
+        };
+        let items = self.palette_items(ctx, mode);
+        let Some(ref mut palette) = self.command_palette else {
+            return;
+        };
+        let (open, action) = palette.show(ctx, items);
+        if !open {
+            self.command_palette = None;
+        }
+
+        match action {
+            Some(PaletteAction::Run(command)) => self.run_command(ctx, command),
+            Some(PaletteAction::OpenFile(path)) => {
+                self.open_file(Some(&path));
+            }
+            Some(PaletteAction::AddColumn(column)) => {
+                if let Some(log_view) = self.active_log_view() {
+                    log_view.add_column(column);
+                }
+            }
+            Some(PaletteAction::RemoveColumn(column)) => {
+                if let Some(log_view) = self.active_log_view() {
+                    log_view.remove_column(&column);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Makes the view of the given file the active, focused tab.
+    fn activate_file(&mut self, file_path: &Path) -> Option<&mut LogView> {
+        let (surface, node, tab) =
+            self.tree
+                .iter_surfaces()
+                .enumerate()
+                .find_map(|(surface, s)| {
+                    s.node_tree()?.iter().enumerate().find_map(|(node, n)| {
+                        let tab = n
+                            .tabs()?
+                            .iter()
+                            .position(|tab| tab.file_path() == file_path)?;
+                        Some((SurfaceIndex(surface), NodeIndex(node), TabIndex(tab)))
+                    })
+                })?;
+        self.tree.set_active_tab((surface, node, tab));
+        self.tree.set_focused_node_and_surface((surface, node));
+        self.tree
+            .find_active_focused()
+            .map(|(_, log_view)| log_view)
+    }
+
+    fn global_search_ui(&mut self, ctx: &egui::Context) {
+        let Some(ref mut global_search) = self.global_search else {
+            return;
+        };
+        let (open, clicked_result) =
+            global_search.show(ctx, &mut self.tree, &mut self.search_library.borrow_mut());
+        if !open {
+            self.global_search = None;
+        }
+        if let Some((file_path, line_num)) = clicked_result {
+            if let Some(log_view) = self.activate_file(&file_path) {
+                log_view.select_line(line_num);
+            }
+        }
+    }
+
+    fn pattern_comparison_ui(&mut self, ctx: &egui::Context) {
+        let Some(ref mut pattern_comparison) = self.pattern_comparison else {
+            return;
+        };
+        let (open, clicked_example) = pattern_comparison.show(ctx, &mut self.tree);
+        if !open {
+            self.pattern_comparison = None;
+        }
+        if let Some((file_path, line_num)) = clicked_example {
+            if let Some(log_view) = self.activate_file(&file_path) {
+                log_view.select_line(line_num);
+            }
+        }
+    }
+
     fn recent_file_menu(&self, ui: &mut Ui) -> Option<PathBuf> {
         for file in self
             .recent_files
@@ -117,6 +374,20 @@ impl eframe::App for TemplateApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         puffin::profile_function!();
 
+        let is_recording_key = self
+            .key_bindings_window
+            .as_ref()
+            .is_some_and(|window| window.is_recording());
+        if self.command_palette.is_none() && !is_recording_key {
+            let dialog_open = self
+                .tree
+                .iter_all_tabs()
+                .any(|(_, log_view)| log_view.has_dialog_open());
+            for command in self.key_bindings.read_commands(ctx, dialog_open) {
+                self.run_command(ctx, command);
+            }
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
@@ -143,6 +414,11 @@ impl eframe::App for TemplateApp {
 
                     ui.separator();
 
+                    if ui.button("Keyboard Shortcuts…").clicked() {
+                        self.key_bindings_window = Some(KeyBindingsWindow::new());
+                        ui.close_menu();
+                    }
+
                     if ui.button("Reset UI").clicked() {
                         ui.memory_mut(|mem| *mem = Default::default());
                         ui.close_menu();
@@ -160,6 +436,66 @@ impl eframe::App for TemplateApp {
                     }
                 }
 
+                if ui
+                    .button("Search All")
+                    .on_hover_text("Search every open file")
+                    .clicked()
+                {
+                    self.global_search
+                        .get_or_insert_with(GlobalSearchWindow::new);
+                }
+
+                if ui
+                    .button("Compare")
+                    .on_hover_text("Compare the message patterns of two open files")
+                    .clicked()
+                {
+                    self.pattern_comparison
+                        .get_or_insert_with(PatternComparisonWindow::new);
+                }
+
+                let go_to_hover_text = match self.key_bindings.shortcut_text(ctx, Command::GoTo) {
+                    Some(shortcut) => format!("Go to a line number or a timestamp ({})", shortcut),
+                    None => "Go to a line number or a timestamp".to_string(),
+                };
+                if ui
+                    .button("Go to…")
+                    .on_hover_text(go_to_hover_text)
+                    .clicked()
+                {
+                    if let Some((_, log_view)) = self.tree.find_active_focused() {
+                        log_view.open_go_to();
+                    }
+                }
+
+                let palette_hover_text =
+                    match self.key_bindings.shortcut_text(ctx, Command::ShowPalette) {
+                        Some(shortcut) => format!("Search all commands ({})", shortcut),
+                        None => "Search all commands".to_string(),
+                    };
+                if ui
+                    .button("Commands")
+                    .on_hover_text(palette_hover_text)
+                    .clicked()
+                {
+                    self.command_palette = Some(CommandPalette::new(PaletteMode::Commands));
+                }
+
+                if let Some((_, log_view)) = self.tree.find_active_focused() {
+                    ui.menu_button("Encoding", |ui| {
+                        let current_encoding = log_view.encoding();
+                        for encoding in TextEncoding::ALL {
+                            if ui
+                                .radio(current_encoding == encoding, encoding.name())
+                                .clicked()
+                            {
+                                log_view.set_encoding(encoding);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+
                 ui.add_space(16.0);
                 egui::widgets::global_dark_light_mode_buttons(ui);
                 egui::warn_if_debug_build(ui);
@@ -188,6 +524,16 @@ impl eframe::App for TemplateApp {
             });
         }
 
+        self.command_palette_ui(ctx);
+        self.global_search_ui(ctx);
+        self.pattern_comparison_ui(ctx);
+
+        if let Some(ref mut key_bindings_window) = self.key_bindings_window {
+            if !key_bindings_window.show(ctx, &mut self.key_bindings) {
+                self.key_bindings_window = None;
+            }
+        }
+
         // Keep painting at least once a second to check for file changes
         ctx.request_repaint_after(Duration::from_secs(1));
     }
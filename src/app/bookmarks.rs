@@ -0,0 +1,136 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use super::log_file_reader::LineNumber;
+
+/// Bookmarked lines and notes on lines of one file.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FileBookmarks {
+    lines: BTreeSet<LineNumber>,
+    notes: BTreeMap<LineNumber, String>,
+}
+
+impl FileBookmarks {
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.notes.is_empty()
+    }
+}
+
+/// Bookmarks and notes of every file, keyed by file path and persisted with the app.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BookmarkStore {
+    files: HashMap<PathBuf, FileBookmarks>,
+}
+
+/// The store is shared by the views of every open file.
+pub type SharedBookmarkStore = Rc<RefCell<BookmarkStore>>;
+
+/// Bookmarks and notes of the file shown by a log view.
+#[derive(Default)]
+pub struct Bookmarks {
+    store: SharedBookmarkStore,
+    file_path: PathBuf,
+}
+
+impl Bookmarks {
+    pub fn new(store: SharedBookmarkStore, file_path: &Path) -> Self {
+        Self {
+            store,
+            file_path: file_path.to_owned(),
+        }
+    }
+
+    fn with_file<T>(&self, f: impl FnOnce(&FileBookmarks) -> T) -> T {
+        match self.store.borrow().files.get(&self.file_path) {
+            Some(file) => f(file),
+            None => f(&FileBookmarks::default()),
+        }
+    }
+
+    fn with_file_mut<T>(&self, f: impl FnOnce(&mut FileBookmarks) -> T) -> T {
+        let mut store = self.store.borrow_mut();
+        let result = f(store.files.entry(self.file_path.clone()).or_default());
+        store.files.retain(|_, file| !file.is_empty());
+        result
+    }
+
+    pub fn contains(&self, line_num: LineNumber) -> bool {
+        self.with_file(|file| file.lines.contains(&line_num))
+    }
+
+    pub fn len(&self) -> usize {
+        self.with_file(|file| file.lines.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bookmarked lines in ascending order.
+    pub fn lines(&self) -> Vec<LineNumber> {
+        self.with_file(|file| file.lines.iter().copied().collect())
+    }
+
+    /// Adds or removes a bookmark. Returns true if the line is now bookmarked.
+    pub fn toggle(&self, line_num: LineNumber) -> bool {
+        self.with_file_mut(|file| file.lines.insert(line_num) || !file.lines.remove(&line_num))
+    }
+
+    pub fn remove(&self, line_num: LineNumber) {
+        self.with_file_mut(|file| file.lines.remove(&line_num));
+    }
+
+    /// Removes every bookmark, keeping the notes.
+    pub fn clear(&self) {
+        self.with_file_mut(|file| file.lines.clear());
+    }
+
+    pub fn note(&self, line_num: LineNumber) -> Option<String> {
+        self.with_file(|file| file.notes.get(&line_num).cloned())
+    }
+
+    pub fn has_note(&self, line_num: LineNumber) -> bool {
+        self.with_file(|file| file.notes.contains_key(&line_num))
+    }
+
+    /// Sets the note on a line. A blank note removes it.
+    pub fn set_note(&self, line_num: LineNumber, note: &str) {
+        self.with_file_mut(|file| {
+            if note.trim().is_empty() {
+                file.notes.remove(&line_num);
+            } else {
+                file.notes.insert(line_num, note.trim_end().to_string());
+            }
+        });
+    }
+
+    /// Annotated lines and their notes, in ascending line order.
+    pub fn notes(&self) -> Vec<(LineNumber, String)> {
+        self.with_file(|file| {
+            file.notes
+                .iter()
+                .map(|(line_num, note)| (*line_num, note.clone()))
+                .collect()
+        })
+    }
+
+    /// Returns the first bookmark after the given line, or the first one overall.
+    pub fn next(&self, after: Option<LineNumber>) -> Option<LineNumber> {
+        self.with_file(|file| match after {
+            Some(line_num) => file.lines.range(line_num + 1..).next().copied(),
+            None => file.lines.first().copied(),
+        })
+    }
+
+    /// Returns the last bookmark before the given line, or the last one overall.
+    pub fn previous(&self, before: Option<LineNumber>) -> Option<LineNumber> {
+        self.with_file(|file| match before {
+            Some(line_num) => file.lines.range(..line_num).next_back().copied(),
+            None => file.lines.last().copied(),
+        })
+    }
+}
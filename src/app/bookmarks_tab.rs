@@ -0,0 +1,161 @@
+use egui::{Align, RichText, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use super::{
+    log_entries_table::add_tool_button,
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+};
+
+/// Lists the bookmarked lines of the file.
+pub struct BookmarksTab {
+    scrolled_to_line: Option<LineNumber>,
+}
+
+impl BookmarksTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            scrolled_to_line: None,
+        })
+    }
+
+    fn toolbar_ui(ui: &mut Ui, viewer_state: &mut LogViewerState) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{} bookmarks", viewer_state.bookmarks.len()));
+
+            ui.separator();
+
+            if add_tool_button(ui, "⏶", "Previous Bookmark").clicked() {
+                viewer_state.select_previous_bookmark();
+            }
+            if add_tool_button(ui, "⏷", "Next Bookmark").clicked() {
+                viewer_state.select_next_bookmark();
+            }
+            if add_tool_button(ui, "🔖", "Toggle Bookmark on Selected Line").clicked() {
+                viewer_state.toggle_bookmark();
+            }
+
+            ui.separator();
+
+            ui.add_enabled_ui(!viewer_state.bookmarks.is_empty(), |ui| {
+                if ui.button("Clear All").clicked() {
+                    viewer_state.bookmarks.clear();
+                }
+            });
+        });
+        ui.separator();
+    }
+}
+
+impl LogViewTabTrait for BookmarksTab {
+    fn title(&self) -> egui::WidgetText {
+        "🔖 Bookmarks".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        Self::toolbar_ui(ui, viewer_state);
+
+        let bookmarks = viewer_state.bookmarks.lines();
+        let mut clicked_line = None;
+        let mut removed_line = None;
+
+        let mut table_builder = TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(20.0))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::remainder().clip(true));
+
+        if self.scrolled_to_line != viewer_state.selected_line_num {
+            self.scrolled_to_line = viewer_state.selected_line_num;
+            if let Some(selected_line) = viewer_state.selected_line_num {
+                if let Ok(row) = bookmarks.binary_search(&selected_line) {
+                    table_builder = table_builder.scroll_to_row(row, Some(Align::Center));
+                }
+            }
+        }
+
+        table_builder
+            .header(20.0, |mut header| {
+                for title in ["", "Line", "Time", "Level", "Message"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, bookmarks.len(), |mut row| {
+                    let line_num = bookmarks[row.index()];
+                    row.set_selected(viewer_state.selected_line_num == Some(line_num));
+
+                    row.col(|ui| {
+                        if add_tool_button(ui, "❌", "Remove Bookmark").clicked() {
+                            removed_line = Some(line_num);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new((line_num + 1).to_string()).monospace());
+                    });
+
+                    let line = if line_num < log_reader.line_count() {
+                        log_reader.read_line(line_num)
+                    } else {
+                        None
+                    };
+                    match line {
+                        Some(line) => match LogFileReader::parse_logline(&line) {
+                            Some(log_entry) => {
+                                for field in ["t", "level", "message"] {
+                                    row.col(|ui| {
+                                        let value = log_entry.field(field);
+                                        if !value.is_null() {
+                                            ui.label(RichText::new(value.to_string()).monospace());
+                                        }
+                                    });
+                                }
+                            }
+                            None => {
+                                row.col(|_| {});
+                                row.col(|_| {});
+                                row.col(|ui| {
+                                    ui.label(RichText::new(line.trim()).monospace());
+                                });
+                            }
+                        },
+                        None => {
+                            row.col(|_| {});
+                            row.col(|_| {});
+                            row.col(|ui| {
+                                ui.label(
+                                    RichText::new("⚠ Line is no longer in the file.")
+                                        .color(ui.visuals().warn_fg_color),
+                                );
+                            });
+                        }
+                    }
+
+                    if row.response().clicked() {
+                        clicked_line = Some(line_num);
+                    }
+                });
+            });
+
+        if let Some(line_num) = removed_line {
+            viewer_state.bookmarks.remove(line_num);
+        }
+        if clicked_line.is_some() {
+            viewer_state.selected_line_num = clicked_line;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::path::PathBuf;
+
+use egui::{Key, RichText};
+
+use super::key_bindings::Command;
+
+/// What the palette lists.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PaletteMode {
+    Commands,
+    RecentFiles,
+    AddColumn,
+    RemoveColumn,
+}
+
+impl PaletteMode {
+    fn hint(&self) -> &'static str {
+        match self {
+            PaletteMode::Commands => "Type a command",
+            PaletteMode::RecentFiles => "Recent file to open",
+            PaletteMode::AddColumn => "Field to add as a column",
+            PaletteMode::RemoveColumn => "Column to remove",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PaletteAction {
+    Run(Command),
+    OpenFile(PathBuf),
+    AddColumn(String),
+    RemoveColumn(String),
+}
+
+pub struct PaletteItem {
+    pub label: String,
+    /// Shown dimmed to the right of the label, e.g. the command's shortcut.
+    pub detail: String,
+    pub action: PaletteAction,
+}
+
+/// A fuzzy-searchable list of commands, opened with Ctrl+Shift+P.
+pub struct CommandPalette {
+    mode: PaletteMode,
+    query: String,
+    selected: usize,
+    request_focus: bool,
+}
+
+impl CommandPalette {
+    pub fn new(mode: PaletteMode) -> Self {
+        Self {
+            mode,
+            query: String::new(),
+            selected: 0,
+            request_focus: true,
+        }
+    }
+
+    pub fn mode(&self) -> PaletteMode {
+        self.mode
+    }
+
+    /// Shows the palette with the items of its mode. Returns false once it should
+    /// be closed, along with the action picked, if any.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        items: Vec<PaletteItem>,
+    ) -> (bool, Option<PaletteAction>) {
+        let mut matches: Vec<(i32, PaletteItem)> = items
+            .into_iter()
+            .filter_map(|item| Some((fuzzy_score(&self.query, &item.label)?, item)))
+            .collect();
+        matches.sort_by_key(|(score, _)| -score);
+
+        // Read the keys before the text box does, as Enter would take its focus away.
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let mut open = !escape;
+        let mut picked = None;
+        if enter {
+            picked = matches
+                .get(self.selected)
+                .map(|(_, item)| item.action.clone());
+        }
+
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size((420.0, 0.0))
+            .anchor(egui::Align2::CENTER_TOP, (0.0, 40.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text(self.mode.hint())
+                        .desired_width(f32::INFINITY),
+                );
+                if self.request_focus {
+                    response.request_focus();
+                    self.request_focus = false;
+                }
+                if response.changed() {
+                    self.selected = 0;
+                }
+                if response.lost_focus() && !enter {
+                    open = false;
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        if matches.is_empty() {
+                            ui.weak("No matches");
+                        }
+                        for (index, (_, item)) in matches.iter().enumerate() {
+                            let is_selected = index == self.selected;
+                            let response = ui
+                                .horizontal(|ui| {
+                                    let response = ui.selectable_label(is_selected, &item.label);
+                                    ui.with_layout(
+                                        egui::Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            ui.label(
+                                                RichText::new(&item.detail).weak().monospace(),
+                                            );
+                                        },
+                                    );
+                                    response
+                                })
+                                .inner;
+                            if is_selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                picked = Some(item.action.clone());
+                            }
+                        }
+                    });
+            });
+
+        (open && picked.is_none(), picked)
+    }
+}
+
+/// Scores how well `query` matches `text` as a case-insensitive subsequence.
+/// Consecutive characters and characters starting a word score higher.
+/// Returns None if `query` isn't a subsequence of `text`.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut text_chars = text.char_indices();
+    // Byte index just past the previous matched character.
+    let mut previous_end: Option<usize> = None;
+
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let query_char = query_char.to_ascii_lowercase();
+        loop {
+            let (index, text_char) = text_chars.next()?;
+            if text_char.to_ascii_lowercase() != query_char {
+                continue;
+            }
+
+            score += 1;
+            let word_start = index == 0
+                || text[..index]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| !c.is_alphanumeric());
+            if word_start {
+                score += 4;
+            }
+            if previous_end == Some(index) {
+                score += 2;
+            }
+            previous_end = Some(index + text_char.len_utf8());
+            break;
+        }
+    }
+
+    // Prefer shorter labels among equally good matches.
+    Some(score * 100 - text.len() as i32)
+}
@@ -0,0 +1,214 @@
+use std::fmt::{self, Display};
+
+use json::JsonValue;
+
+static NULL: JsonValue = JsonValue::Null;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A path to a field nested inside a log entry's JSON object.
+///
+/// Two syntaxes are accepted:
+/// * Dotted paths with optional array indices, e.g. `http.request.method` or `error.stack[0]`.
+/// * JSON Pointers (RFC 6901), e.g. `/http/request/method` or `/error/stack/0`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    /// Parses a field path. Paths starting with `/` are treated as JSON Pointers,
+    /// anything else as a dotted path.
+    pub fn parse(path: &str) -> Self {
+        if path.starts_with('/') {
+            Self::parse_pointer(path)
+        } else {
+            Self::parse_dotted(path)
+        }
+    }
+
+    fn parse_pointer(pointer: &str) -> Self {
+        let segments = pointer
+            .split('/')
+            .skip(1)
+            .map(|token| PathSegment::Key(token.replace("~1", "/").replace("~0", "~")))
+            .collect();
+
+        Self { segments }
+    }
+
+    fn parse_dotted(path: &str) -> Self {
+        let mut segments = vec![];
+
+        for part in path.split('.') {
+            let (key, mut indices) = match part.find('[') {
+                Some(bracket_pos) => part.split_at(bracket_pos),
+                None => (part, ""),
+            };
+
+            if !key.is_empty() {
+                segments.push(PathSegment::Key(key.to_string()));
+            }
+
+            while let Some(rest) = indices.strip_prefix('[') {
+                let Some((index, remainder)) = rest.split_once(']') else {
+                    // Unterminated index, treat the remainder as a plain key.
+                    segments.push(PathSegment::Key(indices.to_string()));
+                    break;
+                };
+                segments.push(match index.parse() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(index.trim_matches(['"', '\'']).to_string()),
+                });
+                indices = remainder;
+            }
+        }
+
+        Self { segments }
+    }
+
+    /// Returns a new path referring to the given key of the field at this path.
+    pub fn join_key(&self, key: &str) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(PathSegment::Key(key.to_string()));
+        Self { segments }
+    }
+
+    /// Returns a new path referring to the given array element of the field at this path.
+    pub fn join_index(&self, index: usize) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(PathSegment::Index(index));
+        Self { segments }
+    }
+
+    /// Number of nested levels below the root object.
+    pub fn depth(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Resolves this path against a JSON value.
+    /// Returns `JsonValue::Null` if any part of the path does not exist.
+    pub fn lookup<'a>(&self, root: &'a JsonValue) -> &'a JsonValue {
+        let mut value = root;
+
+        for segment in &self.segments {
+            value = match (segment, value) {
+                (PathSegment::Key(key), JsonValue::Object(object)) => match object.get(key) {
+                    Some(child) => child,
+                    None => return &NULL,
+                },
+                (PathSegment::Key(key), JsonValue::Array(array)) => {
+                    match key.parse::<usize>().ok().and_then(|i| array.get(i)) {
+                        Some(child) => child,
+                        None => return &NULL,
+                    }
+                }
+                (PathSegment::Index(index), JsonValue::Array(array)) => match array.get(*index) {
+                    Some(child) => child,
+                    None => return &NULL,
+                },
+                (PathSegment::Index(index), JsonValue::Object(object)) => {
+                    match object.get(&index.to_string()) {
+                        Some(child) => child,
+                        None => return &NULL,
+                    }
+                }
+                _ => return &NULL,
+            }
+        }
+
+        value
+    }
+
+    /// Formats the path as a JSON Pointer.
+    pub fn to_pointer(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                PathSegment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+                PathSegment::Index(index) => format!("/{}", index),
+            })
+            .collect()
+    }
+
+    /// True if the path can be written in dotted form without losing information.
+    fn is_dotted_representable(&self) -> bool {
+        self.segments
+            .iter()
+            .enumerate()
+            .all(|(i, segment)| match segment {
+                PathSegment::Key(key) => !key.is_empty() && !key.contains(['.', '[', ']', '/']),
+                PathSegment::Index(_) => i > 0,
+            })
+    }
+}
+
+impl Display for FieldPath {
+    /// Formats the path in dotted form, falling back to a JSON Pointer if
+    /// some key cannot be represented in dotted form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_dotted_representable() {
+            return f.write_str(&self.to_pointer());
+        }
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Looks up a field of a log entry object by key.
+/// Keys that exist verbatim at the top level of the object (including ones containing dots)
+/// take precedence, otherwise the key is parsed as a `FieldPath`.
+pub fn lookup_field<'a>(object: &'a JsonValue, key: &str) -> &'a JsonValue {
+    if object.has_key(key) {
+        &object[key]
+    } else {
+        FieldPath::parse(key).lookup(object)
+    }
+}
+
+/// Every field of a log entry object with its depth, each followed by the fields nested in
+/// it. Top-level keys are used verbatim, nested fields are keyed by their dotted path.
+pub fn collect_fields(object: &JsonValue) -> Vec<(String, usize, &JsonValue)> {
+    let mut fields = vec![];
+    for (key, value) in object.entries() {
+        let path = FieldPath::default().join_key(key);
+        collect_nested_fields(key.to_string(), path, value, &mut fields);
+    }
+    fields
+}
+
+fn collect_nested_fields<'a>(
+    key: String,
+    path: FieldPath,
+    value: &'a JsonValue,
+    fields: &mut Vec<(String, usize, &'a JsonValue)>,
+) {
+    fields.push((key, path.depth(), value));
+
+    match value {
+        JsonValue::Object(object) => {
+            for (child_key, child_value) in object.iter() {
+                let child_path = path.join_key(child_key);
+                collect_nested_fields(child_path.to_string(), child_path, child_value, fields);
+            }
+        }
+        JsonValue::Array(array) => {
+            for (index, child_value) in array.iter().enumerate() {
+                let child_path = path.join_index(index);
+                collect_nested_fields(child_path.to_string(), child_path, child_value, fields);
+            }
+        }
+        _ => {}
+    }
+}
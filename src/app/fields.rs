@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap};
+
+use json::JsonValue;
+
+use super::{
+    field_path::FieldPath,
+    log_analysis::LineAnalysis,
+    log_file_reader::{LineNumber, LogFileReader},
+    query::query_literal,
+};
+
+/// Distinct values counted per field. Values seen after that are only counted as a total.
+const MAX_DISTINCT_VALUES: usize = 1000;
+
+/// Values longer than this are counted, but not kept as distinct values.
+const MAX_VALUE_LEN: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 6] = [
+        ValueType::Null,
+        ValueType::Bool,
+        ValueType::Number,
+        ValueType::String,
+        ValueType::Array,
+        ValueType::Object,
+    ];
+
+    pub fn of(value: &JsonValue) -> Self {
+        match value {
+            JsonValue::Null => ValueType::Null,
+            JsonValue::Boolean(_) => ValueType::Bool,
+            JsonValue::Number(_) => ValueType::Number,
+            JsonValue::Short(_) | JsonValue::String(_) => ValueType::String,
+            JsonValue::Array(_) => ValueType::Array,
+            JsonValue::Object(_) => ValueType::Object,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::Null => "null",
+            ValueType::Bool => "bool",
+            ValueType::Number => "number",
+            ValueType::String => "string",
+            ValueType::Array => "array",
+            ValueType::Object => "object",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// A scalar value of a field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FieldValue {
+    pub value_type: ValueType,
+    pub text: String,
+}
+
+impl FieldValue {
+    /// A query matching the entries whose `field` has this value. Queries treat null
+    /// like a missing field, so the query of null also matches entries without the field.
+    pub fn query(&self, field: &str) -> String {
+        let field = query_literal(field);
+        match self.value_type {
+            ValueType::Null => format!("not exists {}", field),
+            ValueType::String => format!("{} = {}", field, query_literal(&self.text)),
+            _ => format!("{} = {}", field, self.text),
+        }
+    }
+}
+
+/// What is known about one field across the analyzed entries.
+#[derive(Default)]
+pub struct FieldStats {
+    /// Number of entries with the field.
+    pub count: usize,
+    pub type_counts: [usize; ValueType::ALL.len()],
+    values: HashMap<FieldValue, usize>,
+    /// Set once a value couldn't be kept, so the distinct values are incomplete.
+    values_incomplete: bool,
+}
+
+impl FieldStats {
+    fn add(&mut self, value: &JsonValue) {
+        let value_type = ValueType::of(value);
+        self.count += 1;
+        self.type_counts[value_type.index()] += 1;
+
+        if matches!(value_type, ValueType::Array | ValueType::Object) {
+            return;
+        }
+        let text = value.to_string();
+        let value = FieldValue { value_type, text };
+        if let Some(count) = self.values.get_mut(&value) {
+            *count += 1;
+        } else if self.values.len() < MAX_DISTINCT_VALUES && value.text.len() <= MAX_VALUE_LEN {
+            self.values.insert(value, 1);
+        } else {
+            self.values_incomplete = true;
+        }
+    }
+
+    /// Number of distinct scalar values, and whether there may be more.
+    pub fn cardinality(&self) -> (usize, bool) {
+        (self.values.len(), self.values_incomplete)
+    }
+
+    /// Most frequent scalar values with their counts, most frequent first.
+    pub fn top_values(&self, count: usize) -> Vec<(&FieldValue, usize)> {
+        let mut values: Vec<(&FieldValue, usize)> = self
+            .values
+            .iter()
+            .map(|(value, count)| (value, *count))
+            .collect();
+        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.text.cmp(&b.0.text)));
+        values.truncate(count);
+        values
+    }
+}
+
+/// Every field seen in the entries of a file, nested ones included.
+/// Fields are keyed like in the Context tab: top-level keys verbatim, nested fields by
+/// their dotted path. Objects are descended into, arrays are not.
+#[derive(Default)]
+pub struct FieldIndex {
+    fields: BTreeMap<String, FieldStats>,
+    entry_count: usize,
+}
+
+impl FieldIndex {
+    pub fn fields(&self) -> &BTreeMap<String, FieldStats> {
+        &self.fields
+    }
+
+    /// Number of lines that are log entries.
+    pub fn entry_count(&self) -> usize {
+        self.entry_count
+    }
+
+    fn add_field(&mut self, key: String, path: FieldPath, value: &JsonValue) {
+        if let JsonValue::Object(object) = value {
+            for (child_key, child_value) in object.iter() {
+                let child_path = path.join_key(child_key);
+                self.add_field(child_path.to_string(), child_path, child_value);
+            }
+        }
+        self.fields.entry(key).or_default().add(value);
+    }
+}
+
+impl LineAnalysis for FieldIndex {
+    /// Object of a log entry.
+    type Line = Option<JsonValue>;
+
+    fn parse_line(line: &str) -> Option<JsonValue> {
+        LogFileReader::parse_logline(line).map(|log_entry| log_entry.object)
+    }
+
+    fn add_line(&mut self, _line_num: LineNumber, object: Option<JsonValue>) {
+        let Some(object) = object else {
+            return;
+        };
+        self.entry_count += 1;
+        for (key, value) in object.entries() {
+            let path = FieldPath::default().join_key(key);
+            self.add_field(key.to_string(), path, value);
+        }
+    }
+}
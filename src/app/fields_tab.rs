@@ -0,0 +1,354 @@
+use std::time::Duration;
+
+use egui::{Align, Color32, CursorIcon, RichText, Sense, Ui};
+use egui_extras::{Column, TableBuilder};
+use egui_toast::ToastKind;
+
+use super::{
+    fields::{FieldStats, FieldValue, ValueType},
+    filter_chain_tab::{FilterKind, FilterMode, FilterStage},
+    log_entries_table::add_tool_button,
+    log_file_reader::LogFileReader,
+    log_view::{LogViewTabTrait, LogViewerState},
+    stats_tab::percentage,
+};
+
+/// Changes requested from the tables, applied once the field index is unlocked.
+enum FieldAction {
+    Select(Option<String>),
+    AddColumn(String),
+    AddFilter(FilterMode, String),
+}
+
+/// Lists every field of the file's entries, and the most frequent values of the
+/// selected one.
+pub struct FieldsTab {
+    filter_text: String,
+    selected_field: Option<String>,
+    /// Number of values listed for the selected field.
+    top_value_count: usize,
+}
+
+impl FieldsTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            filter_text: String::new(),
+            selected_field: None,
+            top_value_count: 20,
+        })
+    }
+
+    fn toolbar_ui(&mut self, ui: &mut Ui, viewer_state: &LogViewerState) {
+        ui.horizontal(|ui| {
+            let field_index = viewer_state.fields.lock();
+            ui.label(format!(
+                "{} fields in {} entries",
+                field_index.fields().len(),
+                field_index.entry_count()
+            ));
+            drop(field_index);
+            if !viewer_state.fields.is_complete() {
+                let (lines_analyzed, line_count) = viewer_state.fields.progress();
+                ui.spinner();
+                ui.label(format!(
+                    "Analyzed {} of {} lines",
+                    lines_analyzed, line_count
+                ));
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("Filter fields")
+                    .desired_width(200.0),
+            );
+            if !self.filter_text.is_empty() && add_tool_button(ui, "✖", "Clear Filter").clicked()
+            {
+                self.filter_text.clear();
+            }
+        });
+        ui.separator();
+    }
+
+    /// The types of a field's values, with their shares if there are several.
+    fn types_text(field: &FieldStats) -> String {
+        let types: Vec<ValueType> = ValueType::ALL
+            .into_iter()
+            .filter(|value_type| field.type_counts[value_type.index()] > 0)
+            .collect();
+        if types.len() == 1 {
+            return types[0].name().to_string();
+        }
+        types
+            .iter()
+            .map(|value_type| {
+                format!(
+                    "{} {}",
+                    value_type.name(),
+                    percentage(field.type_counts[value_type.index()], field.count)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn cardinality_text(field: &FieldStats) -> String {
+        match field.cardinality() {
+            (0, _) => "-".to_string(),
+            (distinct, false) => distinct.to_string(),
+            (distinct, true) => format!("{}+", distinct),
+        }
+    }
+
+    fn ui_fields(&self, ui: &mut Ui, viewer_state: &LogViewerState) -> Option<FieldAction> {
+        let field_index = viewer_state.fields.lock();
+        let entry_count = field_index.entry_count();
+
+        let filter_text = self.filter_text.to_lowercase();
+        let rows: Vec<(&String, &FieldStats)> = field_index
+            .fields()
+            .iter()
+            .filter(|(key, _)| filter_text.is_empty() || key.to_lowercase().contains(&filter_text))
+            .collect();
+
+        let mut action = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto())
+            .column(Column::auto().at_least(150.0))
+            .column(Column::auto().at_least(70.0))
+            .column(Column::auto().at_least(70.0))
+            .column(Column::remainder().clip(true))
+            .header(20.0, |mut header| {
+                for title in ["", "Field", "Presence", "Distinct", "Types"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, rows.len(), |mut row| {
+                    let (key, field) = rows[row.index()];
+                    row.set_selected(self.selected_field.as_ref() == Some(key));
+
+                    row.col(|ui| {
+                        if !viewer_state.displayed_columns.contains(key)
+                            && add_tool_button(ui, "➕", "Add Column").clicked()
+                        {
+                            action = Some(FieldAction::AddColumn(key.clone()));
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(key).monospace().color(Color32::WHITE));
+                    });
+                    row.col(|ui| {
+                        ui.label(percentage(field.count, entry_count))
+                            .on_hover_text(format!(
+                                "In {} of {} entries",
+                                field.count, entry_count
+                            ));
+                    });
+                    row.col(|ui| {
+                        ui.label(Self::cardinality_text(field));
+                    });
+                    row.col(|ui| {
+                        ui.label(Self::types_text(field));
+                    });
+
+                    if row.response().clicked() {
+                        let selection =
+                            (self.selected_field.as_ref() != Some(key)).then(|| key.clone());
+                        action = Some(FieldAction::Select(selection));
+                    }
+                });
+            });
+
+        action
+    }
+
+    fn value_text(value: &FieldValue) -> RichText {
+        match value.value_type {
+            ValueType::String if value.text.is_empty() => RichText::new("\"\"").weak(),
+            ValueType::String => RichText::new(&value.text).color(Color32::WHITE),
+            _ => RichText::new(&value.text).italics(),
+        }
+        .monospace()
+    }
+
+    /// Hover texts of the filter buttons of a value. A query can't tell a null value from a
+    /// missing field, so the filters of null match entries without the field as well.
+    fn filter_hover_texts(value: &FieldValue) -> (&'static str, &'static str) {
+        match value.value_type {
+            ValueType::Null => (
+                "Include Entries With This Field Missing or Null",
+                "Exclude Entries With This Field Missing or Null",
+            ),
+            _ => (
+                "Include Entries With This Value",
+                "Exclude Entries With This Value",
+            ),
+        }
+    }
+
+    fn ui_values(&mut self, ui: &mut Ui, viewer_state: &LogViewerState) -> Option<FieldAction> {
+        let key = self.selected_field.clone()?;
+        let field_index = viewer_state.fields.lock();
+        let Some(field) = field_index.fields().get(&key) else {
+            ui.label("The field is no longer in the file.");
+            return Some(FieldAction::Select(None));
+        };
+
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&key).monospace().strong());
+            ui.label(format!(
+                "in {} entries, {} distinct values",
+                field.count,
+                Self::cardinality_text(field)
+            ));
+            ui.separator();
+            ui.label("Top");
+            ui.add(egui::DragValue::new(&mut self.top_value_count).clamp_range(1..=1000));
+            if !viewer_state.displayed_columns.contains(&key)
+                && add_tool_button(ui, "➕ Column", "Add Column").clicked()
+            {
+                action = Some(FieldAction::AddColumn(key.clone()));
+            }
+            if add_tool_button(ui, "✖", "Close").clicked() {
+                action = Some(FieldAction::Select(None));
+            }
+        });
+        ui.separator();
+
+        let values = field.top_values(self.top_value_count);
+        if values.is_empty() {
+            ui.label("The field only has arrays or objects as values.");
+            return action;
+        }
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto())
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::remainder().clip(true))
+            .header(20.0, |mut header| {
+                for title in ["Filter", "Count", "Share", "Value"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, values.len(), |mut row| {
+                    let (value, count) = values[row.index()];
+                    let (include_text, exclude_text) = Self::filter_hover_texts(value);
+
+                    row.col(|ui| {
+                        if add_tool_button(ui, "➕", include_text).clicked() {
+                            action = Some(FieldAction::AddFilter(
+                                FilterMode::Include,
+                                value.query(&key),
+                            ));
+                        }
+                        if add_tool_button(ui, "➖", exclude_text).clicked() {
+                            action = Some(FieldAction::AddFilter(
+                                FilterMode::Exclude,
+                                value.query(&key),
+                            ));
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(count.to_string()).monospace());
+                    });
+                    row.col(|ui| {
+                        ui.label(percentage(count, field.count));
+                    });
+                    row.col(|ui| {
+                        if ui
+                            .link(Self::value_text(value))
+                            .on_hover_cursor(CursorIcon::PointingHand)
+                            .on_hover_text(include_text)
+                            .clicked()
+                        {
+                            action = Some(FieldAction::AddFilter(
+                                FilterMode::Include,
+                                value.query(&key),
+                            ));
+                        }
+                    });
+                });
+            });
+
+        action
+    }
+
+    fn apply(&mut self, action: FieldAction, viewer_state: &mut LogViewerState) {
+        match action {
+            FieldAction::Select(field) => self.selected_field = field,
+            FieldAction::AddColumn(key) => {
+                let text = format!("Added column '{}'", key);
+                viewer_state.displayed_columns.push(key);
+                viewer_state.add_toast(ToastKind::Info, text.into(), 2.0);
+            }
+            FieldAction::AddFilter(mode, query) => {
+                let text = match mode {
+                    FilterMode::Include => format!("Added filter '{}'", query),
+                    FilterMode::Exclude => format!("Added filter excluding '{}'", query),
+                };
+                viewer_state
+                    .filters_to_add
+                    .push(FilterStage::new(mode, FilterKind::Query, query));
+                viewer_state.add_toast(ToastKind::Info, text.into(), 2.0);
+            }
+        }
+    }
+}
+
+impl LogViewTabTrait for FieldsTab {
+    fn title(&self) -> egui::WidgetText {
+        "🏷 Fields".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        viewer_state.fields.sync(log_reader);
+        if !viewer_state.fields.is_complete() {
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+
+        self.toolbar_ui(ui, viewer_state);
+
+        let action = if self.selected_field.is_none() {
+            self.ui_fields(ui, viewer_state)
+        } else {
+            let fields_action = egui::TopBottomPanel::top(ui.id().with("fields"))
+                .resizable(true)
+                .default_height(250.0)
+                .show_inside(ui, |ui| self.ui_fields(ui, viewer_state))
+                .inner;
+            let values_action = self.ui_values(ui, viewer_state);
+            fields_action.or(values_action)
+        };
+
+        if let Some(action) = action {
+            self.apply(action, viewer_state);
+        }
+    }
+}
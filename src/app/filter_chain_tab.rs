@@ -0,0 +1,505 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, SystemTime};
+
+use egui::{Checkbox, RichText, Ui};
+use log::error;
+use regex::{Regex, RegexBuilder};
+
+use super::{
+    filtered_log_entries_tab::format_bytes,
+    log_entries_table::{add_tool_button, next_lines_generation, LogEntriesTable, TableLines},
+    log_file_reader::{LineIndex, LineNumber, LogEntry, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState, TabKind},
+    query::Query,
+    search_job::SearchJob,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    Include,
+    Exclude,
+}
+
+impl FilterMode {
+    fn name(&self) -> &'static str {
+        match self {
+            FilterMode::Include => "Include",
+            FilterMode::Exclude => "Exclude",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterKind {
+    Text,
+    Regex,
+    Query,
+}
+
+impl FilterKind {
+    fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Text => "Text",
+            FilterKind::Regex => "Regex",
+            FilterKind::Query => "Query",
+        }
+    }
+}
+
+/// One step of a filter chain. Lines reaching the stage are kept or dropped
+/// depending on whether they match its pattern.
+#[derive(Clone, PartialEq)]
+pub struct FilterStage {
+    pub enabled: bool,
+    pub mode: FilterMode,
+    pub kind: FilterKind,
+    pub pattern: String,
+    pub case_sensitive: bool,
+    error: Option<String>,
+}
+
+impl FilterStage {
+    pub fn new(mode: FilterMode, kind: FilterKind, pattern: String) -> Self {
+        Self {
+            enabled: true,
+            mode,
+            kind,
+            pattern,
+            case_sensitive: false,
+            error: None,
+        }
+    }
+
+    fn compile(&self) -> Result<StageMatcher, String> {
+        match self.kind {
+            FilterKind::Text | FilterKind::Regex => {
+                let pattern = match self.kind {
+                    FilterKind::Text => regex::escape(&self.pattern),
+                    _ => self.pattern.clone(),
+                };
+                RegexBuilder::new(&pattern)
+                    .case_insensitive(!self.case_sensitive)
+                    .build()
+                    .map(StageMatcher::Regex)
+                    .map_err(|e| e.to_string())
+            }
+            FilterKind::Query => Query::parse(&self.pattern)
+                .map(StageMatcher::Query)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+enum StageMatcher {
+    Regex(Regex),
+    Query(Query),
+}
+
+/// A stage prepared to run on the filter worker.
+struct CompiledStage {
+    index: usize,
+    mode: FilterMode,
+    matcher: StageMatcher,
+}
+
+impl CompiledStage {
+    fn is_match(&self, line: &str, log_entry: &mut Option<Option<LogEntry>>) -> bool {
+        match &self.matcher {
+            StageMatcher::Regex(regex) => regex.is_match(line),
+            StageMatcher::Query(query) => log_entry
+                .get_or_insert_with(|| LogFileReader::parse_logline(line))
+                .as_ref()
+                .is_some_and(|log_entry| query.matches(log_entry)),
+        }
+    }
+}
+
+/// Narrows down the log with a sequence of include/exclude stages.
+/// Each enabled stage only sees the lines kept by the stages before it.
+pub struct FilterChainTab {
+    stages: Vec<FilterStage>,
+    /// Number of lines matched by each stage in the last run, indexed like `stages`.
+    hit_counts: Arc<Vec<AtomicUsize>>,
+    filter_results: Vec<LineNumber>,
+    /// Replaced whenever `filter_results` is replaced rather than appended to.
+    results_generation: usize,
+    /// Results of a re-run after the file changed, shown once the run completes.
+    pending_results: Option<Vec<LineNumber>>,
+    /// Counters of the re-run, swapped in together with `pending_results`.
+    pending_hit_counts: Option<Arc<Vec<AtomicUsize>>>,
+    /// The load of the file and the stages the results are for, so that re-running
+    /// the filter after lines were appended only filters the new lines.
+    filtered: Option<(LineIndex, Vec<FilterStage>)>,
+    filter_job: Option<SearchJob>,
+    highlight: Option<Regex>,
+    log_entries_table: LogEntriesTable,
+    stages_changed: bool,
+    last_filter_time: Option<SystemTime>,
+}
+
+impl FilterChainTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            stages: vec![FilterStage::new(
+                FilterMode::Include,
+                FilterKind::Text,
+                String::new(),
+            )],
+            hit_counts: Default::default(),
+            filter_results: vec![],
+            results_generation: next_lines_generation(),
+            pending_results: None,
+            pending_hit_counts: None,
+            filtered: None,
+            filter_job: None,
+            highlight: None,
+            log_entries_table: LogEntriesTable::new(),
+            stages_changed: false,
+            last_filter_time: None,
+        })
+    }
+
+    /// True if at least one enabled stage has a pattern.
+    fn is_filtering(&self) -> bool {
+        self.stages
+            .iter()
+            .any(|stage| stage.enabled && !stage.pattern.is_empty())
+    }
+
+    fn replace_results(&mut self, results: Vec<LineNumber>) {
+        self.filter_results = results;
+        self.results_generation = next_lines_generation();
+    }
+
+    /// Compiles the enabled stages and starts filtering on a worker thread.
+    /// A re-run keeps showing the current results and hit counts until it finishes,
+    /// or adds to them if the file has only grown since.
+    fn execute_filter(&mut self, log_reader: &LogFileReader, repeat: bool) {
+        let line_index = log_reader.line_index();
+        let first_line = match self.filtered.take() {
+            Some((filtered_index, filtered_stages))
+                if repeat
+                    && filtered_stages == self.stages
+                    && line_index.extends(&filtered_index, filtered_index.line_count()) =>
+            {
+                filtered_index.line_count()
+            }
+            _ => 0,
+        };
+
+        self.last_filter_time = Some(SystemTime::now());
+        self.filter_job = None;
+        self.pending_results = None;
+        self.pending_hit_counts = None;
+
+        let mut compiled_stages = vec![];
+        let mut highlight_patterns = vec![];
+        for (index, stage) in self.stages.iter_mut().enumerate() {
+            stage.error = None;
+            if !stage.enabled || stage.pattern.is_empty() {
+                continue;
+            }
+            match stage.compile() {
+                Ok(matcher) => {
+                    if let (FilterMode::Include, StageMatcher::Regex(regex)) =
+                        (stage.mode, &matcher)
+                    {
+                        highlight_patterns.push(format!(
+                            "(?{}:{})",
+                            if stage.case_sensitive { "-i" } else { "i" },
+                            regex.as_str()
+                        ));
+                    }
+                    compiled_stages.push(CompiledStage {
+                        index,
+                        mode: stage.mode,
+                        matcher,
+                    });
+                }
+                Err(e) => stage.error = Some(e),
+            }
+        }
+
+        if self.stages.iter().any(|stage| stage.error.is_some()) {
+            return;
+        }
+
+        self.highlight = if highlight_patterns.is_empty() {
+            None
+        } else {
+            Regex::new(&highlight_patterns.join("|")).ok()
+        };
+
+        let new_hit_counts = || Arc::new(self.stages.iter().map(|_| AtomicUsize::new(0)).collect());
+
+        if !self.is_filtering() {
+            self.hit_counts = new_hit_counts();
+            self.replace_results(vec![]);
+            return;
+        }
+
+        let hit_counts = if !repeat {
+            self.hit_counts = new_hit_counts();
+            self.replace_results(vec![]);
+            self.hit_counts.clone()
+        } else if first_line == 0 {
+            let hit_counts = new_hit_counts();
+            self.pending_results = Some(vec![]);
+            self.pending_hit_counts = Some(hit_counts.clone());
+            hit_counts
+        } else {
+            self.hit_counts.clone()
+        };
+
+        self.filter_job = Some(SearchJob::start_line_filter(
+            line_index.clone(),
+            first_line,
+            move |_, line| {
+                let mut log_entry = None;
+                for stage in &compiled_stages {
+                    let is_match = stage.is_match(line, &mut log_entry);
+                    if is_match {
+                        hit_counts[stage.index].fetch_add(1, Ordering::Relaxed);
+                    }
+                    if is_match != (stage.mode == FilterMode::Include) {
+                        return false;
+                    }
+                }
+                true
+            },
+        ));
+        self.filtered = Some((line_index, self.stages.clone()));
+    }
+
+    /// Collects results streamed from the running filter.
+    fn poll_filter(&mut self, ui: &Ui) {
+        let Some(filter_job) = &mut self.filter_job else {
+            return;
+        };
+
+        match self.pending_results {
+            Some(ref mut pending_results) => filter_job.poll(pending_results),
+            None => filter_job.poll(&mut self.filter_results),
+        }
+
+        if !filter_job.is_finished() {
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+            return;
+        }
+
+        if let Some(error) = filter_job.error() {
+            error!("Failed to filter: {:?}", error);
+            self.filtered = None;
+        } else if let Some(pending_results) = self.pending_results.take() {
+            self.replace_results(pending_results);
+            if let Some(pending_hit_counts) = self.pending_hit_counts.take() {
+                self.hit_counts = pending_hit_counts;
+            }
+        }
+        self.pending_results = None;
+        self.pending_hit_counts = None;
+        self.filter_job = None;
+    }
+
+    fn ui_stages(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if add_tool_button(ui, "➕", "Add Stage").clicked() {
+                self.stages.push(FilterStage::new(
+                    FilterMode::Include,
+                    FilterKind::Text,
+                    String::new(),
+                ));
+            }
+            if ui.button("Apply").clicked() {
+                self.stages_changed = true;
+            }
+
+            if let Some(ref filter_job) = self.filter_job {
+                let (bytes_scanned, total_bytes) = filter_job.progress();
+                ui.spinner();
+                ui.label(format!(
+                    "Filtering... {} of {}",
+                    format_bytes(bytes_scanned),
+                    format_bytes(total_bytes)
+                ));
+                if ui.button("Cancel").clicked() {
+                    self.filter_job = None;
+                    self.pending_results = None;
+                    self.pending_hit_counts = None;
+                    self.filtered = None;
+                }
+            } else if self.is_filtering() {
+                ui.label(format!("{} lines", self.filter_results.len()));
+            }
+        });
+
+        let mut stage_to_remove = None;
+        let mut stage_to_move_up = None;
+
+        for (index, stage) in self.stages.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add(Checkbox::without_text(&mut stage.enabled))
+                    .on_hover_text("Enable Stage")
+                    .changed()
+                {
+                    self.stages_changed = true;
+                }
+
+                egui::ComboBox::from_id_source(ui.id().with(("mode", index)))
+                    .width(70.0)
+                    .selected_text(stage.mode.name())
+                    .show_ui(ui, |ui| {
+                        for mode in [FilterMode::Include, FilterMode::Exclude] {
+                            if ui
+                                .selectable_value(&mut stage.mode, mode, mode.name())
+                                .changed()
+                            {
+                                self.stages_changed = true;
+                            }
+                        }
+                    });
+
+                egui::ComboBox::from_id_source(ui.id().with(("kind", index)))
+                    .width(60.0)
+                    .selected_text(stage.kind.name())
+                    .show_ui(ui, |ui| {
+                        for kind in [FilterKind::Text, FilterKind::Regex, FilterKind::Query] {
+                            if ui
+                                .selectable_value(&mut stage.kind, kind, kind.name())
+                                .changed()
+                            {
+                                self.stages_changed = true;
+                            }
+                        }
+                    });
+
+                let hint_text = match stage.kind {
+                    FilterKind::Text => "text",
+                    FilterKind::Regex => "regex",
+                    FilterKind::Query => "tag = db and level >= WARNING",
+                };
+                let pattern_response = ui.add(
+                    egui::TextEdit::singleline(&mut stage.pattern)
+                        .hint_text(hint_text)
+                        .desired_width(300.0),
+                );
+                if pattern_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    self.stages_changed = true;
+                }
+
+                if stage.kind != FilterKind::Query
+                    && ui.selectable_label(stage.case_sensitive, "Case").clicked()
+                {
+                    stage.case_sensitive = !stage.case_sensitive;
+                    self.stages_changed = true;
+                }
+
+                let hit_count = self
+                    .hit_counts
+                    .get(index)
+                    .map(|count| count.load(Ordering::Relaxed));
+                match hit_count {
+                    Some(hit_count) if stage.enabled && !stage.pattern.is_empty() => {
+                        ui.label(format!("{} hits", hit_count))
+                    }
+                    _ => ui.label("-"),
+                };
+
+                if index > 0 && add_tool_button(ui, "⏶", "Move Stage Up").clicked() {
+                    stage_to_move_up = Some(index);
+                }
+                if add_tool_button(ui, "❌", "Remove Stage").clicked() {
+                    stage_to_remove = Some(index);
+                }
+
+                if let Some(ref error) = stage.error {
+                    ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                }
+            });
+        }
+
+        if let Some(index) = stage_to_move_up {
+            self.stages.swap(index - 1, index);
+            self.stages_changed = true;
+        }
+        if let Some(index) = stage_to_remove {
+            self.stages.remove(index);
+            self.stages_changed = true;
+        }
+
+        ui.separator();
+    }
+}
+
+impl LogViewTabTrait for FilterChainTab {
+    fn title(&self) -> egui::WidgetText {
+        let enabled_stages = self
+            .stages
+            .iter()
+            .filter(|stage| stage.enabled && !stage.pattern.is_empty())
+            .count();
+        match enabled_stages {
+            0 => "🔎 Filters".into(),
+            1 => "🔎 Filters (1 stage)".into(),
+            n => format!("🔎 Filters ({} stages)", n).into(),
+        }
+    }
+
+    fn kind(&self) -> TabKind {
+        TabKind::Filters
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        if !viewer_state.filters_to_add.is_empty() {
+            // Stages added from other tabs replace the empty stage a chain starts with.
+            self.stages.retain(|stage| !stage.pattern.is_empty());
+            self.stages.append(&mut viewer_state.filters_to_add);
+            self.stages_changed = true;
+        }
+
+        self.poll_filter(ui);
+        self.ui_stages(ui);
+
+        if self.stages_changed {
+            self.stages_changed = false;
+            self.execute_filter(log_reader, false);
+        } else if let (None, Some(last_filter_time), Some(load_time)) = (
+            &self.filter_job,
+            self.last_filter_time,
+            log_reader.load_time_point(),
+        ) {
+            // A running filter is left to finish, then re-run if the file changed meanwhile.
+            if last_filter_time < load_time {
+                self.execute_filter(log_reader, true);
+            }
+        }
+
+        let filtered_entries = if self.is_filtering() {
+            Some(TableLines::Lines(
+                &self.filter_results,
+                self.results_generation,
+            ))
+        } else {
+            None
+        };
+
+        self.log_entries_table.ui(
+            ui,
+            log_reader,
+            viewer_state,
+            filtered_entries,
+            None,
+            self.highlight.as_ref(),
+            |_| {},
+        );
+    }
+}
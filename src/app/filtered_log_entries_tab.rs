@@ -1,54 +1,73 @@
-use std::{
-    fs::File,
-    io,
-    path::{Path, PathBuf},
-};
-use std::time::SystemTime;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
-use egui::{Button, CursorIcon, Ui};
-use grep::searcher::{Searcher, sinks::Lossy};
-use grep_regex::RegexMatcherBuilder;
+use egui::{Button, CursorIcon, RichText, Ui};
+use egui_toast::ToastKind;
 use log::error;
+use regex::Regex;
 
 use super::{
-    log_entries_table::LogEntriesTable,
-    log_file_reader::{LineNumber, LogFileReader},
-    log_view::{LogViewerState, LogViewTabTrait},
+    log_entries_table::{next_lines_generation, LogEntriesTable, TableLines},
+    log_file_reader::{LineIndex, LineNumber, LogFileReader},
+    log_view::{LogViewerState, LogViewTabTrait, Navigation, TabKind},
+    search_job::{SearchJob, SearchOptions},
+    search_library::{SearchLibrary, SearchParams},
 };
 
-#[derive(Debug)]
-enum SearchError {
-    IoError(io::Error),
-    GrepError(grep_regex::Error),
-}
-
-impl From<io::Error> for SearchError {
-    fn from(value: io::Error) -> Self {
-        SearchError::IoError(value)
+/// Formats a byte count for display, e.g. "12.3 MB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
-}
-
-impl From<grep_regex::Error> for SearchError {
-    fn from(value: grep_regex::Error) -> Self {
-        SearchError::GrepError(value)
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
     }
 }
 
-type SearchResult = Result<Vec<LineNumber>, SearchError>;
-
-struct SearchOptions {
-    case_sensitive: bool,
-    whole_word: bool,
-    regex: bool,
+/// Search results along with the lines around them, like `grep -C`.
+#[derive(Default)]
+struct ResultsWithContext {
+    /// Generation of the results and context the lines were computed for. The lines only
+    /// grow while these stay the same, so they keep their own generation.
+    identity: Option<(usize, usize)>,
+    /// Number of results and lines in the file the lines were computed from.
+    computed_for: (usize, LineNumber),
+    lines: Vec<LineNumber>,
+    generation: usize,
 }
 
-impl Default for SearchOptions {
-    fn default() -> Self {
-        Self {
-            case_sensitive: false,
-            whole_word: false,
-            regex: false,
+impl ResultsWithContext {
+    fn update(
+        &mut self,
+        results: &[LineNumber],
+        results_generation: usize,
+        context_lines: usize,
+        line_count: LineNumber,
+    ) -> TableLines<'_> {
+        let identity = (results_generation, context_lines);
+        if self.identity != Some(identity) {
+            self.identity = Some(identity);
+            self.computed_for = (0, 0);
+            self.generation = next_lines_generation();
         }
+        if self.computed_for != (results.len(), line_count) {
+            self.computed_for = (results.len(), line_count);
+            self.lines.clear();
+            for &line_num in results {
+                let start = line_num
+                    .saturating_sub(context_lines)
+                    .max(self.lines.last().map_or(0, |&last| last + 1));
+                let end = (line_num + context_lines + 1).min(line_count);
+                self.lines.extend(start..end);
+            }
+        }
+        TableLines::Lines(&self.lines, self.generation)
     }
 }
 
@@ -56,11 +75,32 @@ pub struct FilteredLogEntriesTab {
     log_file_path: PathBuf,
     editable_search_term: String,
     search_term: String,
+    editable_search_field: String,
+    search_field: String,
     search_results: Vec<LineNumber>,
+    /// Replaced whenever `search_results` is replaced rather than appended to.
+    results_generation: usize,
+    search_error: Option<String>,
     search_options: SearchOptions,
+    search_job: Option<SearchJob>,
+    highlight: Option<Regex>,
+    /// Set when a new search is executed, so its highlight is shared with the other tabs.
+    publish_highlight: bool,
+    search_cancelled: bool,
+    /// Results of a repeated search, shown once the search completes so the
+    /// previous results stay visible in the meantime.
+    pending_results: Option<Vec<LineNumber>>,
+    /// The load of the file and the search the results are for, so that repeating the
+    /// search after lines were appended only searches the new lines.
+    searched: Option<(LineIndex, SearchParams)>,
     log_entries_table: LogEntriesTable,
     repeat_search: bool,
     last_search_time: Option<SystemTime>,
+    /// Name entered for saving the current search.
+    save_name: String,
+    /// Lines shown before and after each result.
+    context_lines: usize,
+    results_with_context: ResultsWithContext,
 }
 
 impl FilteredLogEntriesTab {
@@ -69,105 +109,278 @@ impl FilteredLogEntriesTab {
             log_file_path,
             search_term: Default::default(),
             search_results: vec![],
+            results_generation: next_lines_generation(),
+            search_error: None,
             search_options: Default::default(),
+            search_job: None,
+            highlight: None,
+            publish_highlight: false,
+            search_cancelled: false,
+            pending_results: None,
+            searched: None,
             editable_search_term: Default::default(),
+            search_field: Default::default(),
+            editable_search_field: Default::default(),
             log_entries_table: LogEntriesTable::new(),
             repeat_search: true,
             last_search_time: None,
+            save_name: String::new(),
+            context_lines: 0,
+            results_with_context: Default::default(),
         })
     }
 
-    fn search(options: &SearchOptions, file_path: &Path, search_text: &str) -> SearchResult {
-        // If regex is turned off, escape the search text to literals.
-        let escaped_search_text = if !options.regex {
-            Some(regex::escape(search_text))
-        } else {
-            None
-        };
-
-        // Take a reference to escaped text (present if regex searching is off), or the search text if it's on.
-        let pattern = if let Some(ref escaped_text) = escaped_search_text {
-            escaped_text
-        } else {
-            search_text
-        };
-
-        // Build a grep matcher and searcher matching the options
-        let matcher = RegexMatcherBuilder::new()
-            .case_insensitive(!options.case_sensitive)
-            .word(options.whole_word)
-            .build(&pattern)?;
-        let mut searcher = Searcher::new();
+    /// Opens a tab that runs the given search once the file is loaded.
+    pub fn with_search(log_file_path: PathBuf, params: SearchParams) -> Box<Self> {
+        let mut tab = Self::new(log_file_path);
+        tab.set_search_params(params);
+        tab.publish_highlight = true;
+        tab
+    }
 
-        // Store line numbers of all matches
-        let mut matches: Vec<LineNumber> = vec![];
+    fn search_params(&self) -> SearchParams {
+        SearchParams {
+            term: self.editable_search_term.clone(),
+            field: self.editable_search_field.trim().to_string(),
+            options: self.search_options.clone(),
+        }
+    }
 
-        searcher.search_file(
-            matcher,
-            &File::open(file_path)?,
-            Lossy(|line_num, _line| {
-                let zero_based_line_num = line_num - 1;
-                matches.push(zero_based_line_num as LineNumber);
-                Ok(true)
-            }),
-        )?;
+    fn set_search_params(&mut self, params: SearchParams) {
+        self.editable_search_term = params.term;
+        self.editable_search_field = params.field;
+        self.search_options = params.options;
+    }
 
-        Ok(matches)
+    /// Executes the search entered by the user and adds it to the history.
+    fn start_search(&mut self, log_reader: &LogFileReader, search_library: &mut SearchLibrary) {
+        search_library.record(self.search_params());
+        self.execute_search(log_reader, false);
     }
 
-    fn execute_search(&mut self) {
+    /// Starts searching on a worker thread, cancelling any search in progress.
+    /// A repeated search keeps showing the current results until it finishes, or adds to
+    /// them if the file has only grown since.
+    fn execute_search(&mut self, log_reader: &LogFileReader, repeat: bool) {
+        let line_index = log_reader.line_index();
+        let params = self.search_params();
+        let first_line = match self.searched.take() {
+            Some((searched_index, searched_params))
+                if repeat
+                    && searched_params == params
+                    && line_index.extends(&searched_index, searched_index.line_count()) =>
+            {
+                searched_index.line_count()
+            }
+            _ => 0,
+        };
+
         self.search_term = self.editable_search_term.clone();
+        self.search_field = self.editable_search_field.trim().to_string();
         self.last_search_time = Some(SystemTime::now());
+        self.search_error = None;
+        self.search_cancelled = false;
+        self.search_job = None;
+        self.pending_results = None;
+        self.highlight = self.search_options.highlight_regex(&self.search_term);
+        self.publish_highlight |= !repeat;
 
         if self.search_term.is_empty() {
-            self.search_results.clear();
+            self.replace_results(vec![]);
+            return;
+        }
+
+        if !repeat {
+            self.replace_results(vec![]);
+        } else if first_line == 0 {
+            self.pending_results = Some(vec![]);
+        }
+
+        self.search_job = Some(SearchJob::start(
+            &self.search_options,
+            line_index.clone(),
+            first_line,
+            &self.search_term,
+            &self.search_field,
+        ));
+        self.searched = Some((line_index, params));
+    }
+
+    fn replace_results(&mut self, results: Vec<LineNumber>) {
+        self.search_results = results;
+        self.results_generation = next_lines_generation();
+    }
+
+    fn cancel_search(&mut self) {
+        if let Some(search_job) = self.search_job.take() {
+            search_job.cancel();
+            self.pending_results = None;
+            self.searched = None;
+            self.search_cancelled = true;
+        }
+    }
+
+    /// Collects results streamed from the running search.
+    fn poll_search(&mut self, ui: &Ui) {
+        let Some(search_job) = &mut self.search_job else {
             return;
+        };
+
+        match self.pending_results {
+            Some(ref mut pending_results) => search_job.poll(pending_results),
+            None => search_job.poll(&mut self.search_results),
         }
 
-        match Self::search(&self.search_options, &self.log_file_path, &self.search_term) {
-            Ok(results) => {
-                self.search_results = results;
+        if !search_job.is_finished() {
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+            return;
+        }
+
+        if let Some(error) = search_job.error() {
+            error!("Failed to search {:?}: {:?}", self.log_file_path, error);
+            self.search_error = Some(error.to_string());
+            self.searched = None;
+            self.replace_results(vec![]);
+        } else if let Some(pending_results) = self.pending_results.take() {
+            self.replace_results(pending_results);
+        }
+        self.pending_results = None;
+        self.search_job = None;
+    }
+
+    /// Selects the search result after or before the selected line.
+    fn select_search_hit(&self, viewer_state: &mut LogViewerState, next: bool) {
+        let search_hit = match viewer_state.selected_line_num {
+            Some(selected_line) if next => {
+                let index = self.search_results.partition_point(|&l| l <= selected_line);
+                self.search_results.get(index)
             }
-            Err(error) => error!("Failed to search: {:?}", error),
+            Some(selected_line) => {
+                let index = self.search_results.partition_point(|&l| l < selected_line);
+                index.checked_sub(1).map(|index| &self.search_results[index])
+            }
+            None if next => self.search_results.first(),
+            None => self.search_results.last(),
+        };
+
+        match search_hit {
+            Some(&line_num) => viewer_state.selected_line_num = Some(line_num),
+            None => viewer_state.add_toast(ToastKind::Info, "No more search results.".into(), 2.0),
         }
     }
 
-    fn ui_search(&mut self, ui: &mut Ui) {
+    fn ui_search(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &LogFileReader,
+        viewer_state: &LogViewerState,
+    ) {
+        let mut search_library = viewer_state.search_library.borrow_mut();
         ui.horizontal(|ui| {
-            ui.label("Search text:");
+            ui.label(if self.search_options.query {
+                "Query:"
+            } else {
+                "Search text:"
+            });
 
-            if ui
-                .text_edit_singleline(&mut self.editable_search_term)
-                .lost_focus()
-                && ui.input(|i| i.key_pressed(egui::Key::Enter))
-            {
-                self.execute_search();
+            let hint_text = if self.search_options.query {
+                "level >= WARNING and not message ~ /heartbeat/"
+            } else {
+                ""
+            };
+            let search_term_response = ui.add(
+                egui::TextEdit::singleline(&mut self.editable_search_term).hint_text(hint_text),
+            );
+            if viewer_state.focus_search {
+                search_term_response.request_focus();
             }
-
-            if ui
-                .selectable_label(self.search_options.case_sensitive, "Case")
-                .clicked()
-            {
-                self.search_options.case_sensitive = !self.search_options.case_sensitive;
+            if search_term_response.changed() {
+                self.cancel_search();
             }
-            if ui
-                .selectable_label(self.search_options.whole_word, "Word")
-                .clicked()
+            if search_term_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
             {
-                self.search_options.whole_word = !self.search_options.whole_word;
+                self.start_search(log_reader, &mut search_library);
             }
+
+            ui.menu_button("🕘", |ui| {
+                if let Some(params) = self.ui_search_library(ui, &mut search_library) {
+                    self.set_search_params(params);
+                    self.start_search(log_reader, &mut search_library);
+                    ui.close_menu();
+                }
+            })
+            .response
+            .on_hover_text("Saved Searches and History");
+
+            ui.add_enabled_ui(!self.search_options.query, |ui| {
+                ui.label("in field:");
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.editable_search_field)
+                            .hint_text("any")
+                            .desired_width(120.0),
+                    )
+                    .on_hover_text(
+                        "Only match within this field, e.g. http.request.method or /error/stack/0",
+                    )
+                    .lost_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                {
+                    self.start_search(log_reader, &mut search_library);
+                }
+
+                if ui
+                    .selectable_label(self.search_options.case_sensitive, "Case")
+                    .clicked()
+                {
+                    self.search_options.case_sensitive = !self.search_options.case_sensitive;
+                }
+                if ui
+                    .selectable_label(self.search_options.whole_word, "Word")
+                    .clicked()
+                {
+                    self.search_options.whole_word = !self.search_options.whole_word;
+                }
+                if ui
+                    .selectable_label(self.search_options.regex, "Regex")
+                    .clicked()
+                {
+                    self.search_options.regex = !self.search_options.regex;
+                }
+            });
+
             if ui
-                .selectable_label(self.search_options.regex, "Regex")
+                .selectable_label(self.search_options.query, "Query")
+                .on_hover_text("Filter by field, e.g. level >= WARNING and duration_ms > 500")
                 .clicked()
             {
-                self.search_options.regex = !self.search_options.regex;
+                self.search_options.query = !self.search_options.query;
             }
 
             if ui.button("Search").clicked() {
-                self.execute_search();
+                self.start_search(log_reader, &mut search_library);
             }
 
-            if !self.search_term.is_empty() {
+            if let Some(ref search_job) = self.search_job {
+                let (bytes_scanned, total_bytes) = search_job.progress();
+                ui.spinner();
+                ui.label(format!(
+                    "Searching... {} of {}, {} results",
+                    format_bytes(bytes_scanned),
+                    format_bytes(total_bytes),
+                    self.search_results.len()
+                ));
+                if ui.button("Cancel").clicked() {
+                    self.cancel_search();
+                }
+            } else if let Some(ref search_error) = self.search_error {
+                ui.label(RichText::new(search_error).color(ui.visuals().error_fg_color));
+            } else if self.search_cancelled {
+                ui.label(format!(
+                    "Search cancelled, {} results",
+                    self.search_results.len()
+                ));
+            } else if !self.search_term.is_empty() {
                 match self.search_results.is_empty() {
                     true => ui.label("No results"),
                     false => ui.label(format!("{} results", self.search_results.len())),
@@ -177,6 +390,73 @@ impl FilteredLogEntriesTab {
 
         ui.separator();
     }
+
+    /// Lists saved searches and the search history, and saves the current search.
+    /// Returns the search picked, if any.
+    fn ui_search_library(
+        &mut self,
+        ui: &mut Ui,
+        search_library: &mut SearchLibrary,
+    ) -> Option<SearchParams> {
+        let mut picked = None;
+        ui.set_min_width(240.0);
+
+        ui.label(RichText::new("Saved Searches").strong());
+        if search_library.saved().is_empty() {
+            ui.weak("None yet");
+        }
+        let mut search_to_remove = None;
+        for saved_search in search_library.saved() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button(&saved_search.name)
+                    .on_hover_text(saved_search.params.summary())
+                    .clicked()
+                {
+                    picked = Some(saved_search.params.clone());
+                }
+                if ui.small_button("❌").on_hover_text("Delete").clicked() {
+                    search_to_remove = Some(saved_search.name.clone());
+                }
+            });
+        }
+        if let Some(name) = search_to_remove {
+            search_library.remove_saved(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.save_name)
+                    .hint_text("Name")
+                    .desired_width(140.0),
+            );
+            let can_save =
+                !self.save_name.trim().is_empty() && !self.editable_search_term.is_empty();
+            if ui
+                .add_enabled(can_save, Button::new("Save Current"))
+                .clicked()
+            {
+                search_library.save(&self.save_name, self.search_params());
+                self.save_name.clear();
+            }
+        });
+
+        ui.separator();
+        ui.label(RichText::new("History").strong());
+        if search_library.history().is_empty() {
+            ui.weak("No searches yet");
+        }
+        for params in search_library.history() {
+            if ui.button(params.summary()).clicked() {
+                picked = Some(params.clone());
+            }
+        }
+        if !search_library.history().is_empty() && ui.button("Clear History").clicked() {
+            search_library.clear_history();
+        }
+
+        picked
+    }
 }
 
 impl LogViewTabTrait for FilteredLogEntriesTab {
@@ -188,6 +468,10 @@ impl LogViewTabTrait for FilteredLogEntriesTab {
         }
     }
 
+    fn kind(&self) -> TabKind {
+        TabKind::Search
+    }
+
     fn ui(
         &mut self,
         ui: &mut Ui,
@@ -195,24 +479,54 @@ impl LogViewTabTrait for FilteredLogEntriesTab {
         viewer_state: &mut LogViewerState,
     ) {
         let mut repeat_search = self.repeat_search;
+        let mut context_lines = self.context_lines;
+
+        self.poll_search(ui);
+        self.ui_search(ui, log_reader, viewer_state);
+        viewer_state.focus_search = false;
 
-        self.ui_search(ui);
+        match viewer_state.navigation {
+            Some(Navigation::NextSearchHit) => self.select_search_hit(viewer_state, true),
+            Some(Navigation::PreviousSearchHit) => self.select_search_hit(viewer_state, false),
+            _ => {}
+        }
 
-        if repeat_search && log_reader.load_time_point().is_some() {
+        if self.publish_highlight {
+            viewer_state.search_highlight = self.highlight.clone();
+            self.publish_highlight = false;
+        }
+
+        // A running search is left to finish, then repeated if the file changed meanwhile.
+        if repeat_search && self.search_job.is_none() && log_reader.load_time_point().is_some() {
             let search_needed = match self.last_search_time {
                 None => true,
                 Some(last_search_time) => last_search_time < log_reader.load_time_point().unwrap(),
             };
             if search_needed {
-                self.execute_search();
+                self.execute_search(log_reader, true);
             }
         }
 
+        let (lines, context_of_hits) = if self.context_lines > 0 {
+            let lines = self.results_with_context.update(
+                &self.search_results,
+                self.results_generation,
+                self.context_lines,
+                log_reader.line_count(),
+            );
+            (lines, Some(self.search_results.as_slice()))
+        } else {
+            let lines = TableLines::Lines(&self.search_results, self.results_generation);
+            (lines, None)
+        };
+
         self.log_entries_table.ui(
             ui,
             log_reader,
             viewer_state,
-            Some(&self.search_results),
+            Some(lines),
+            context_of_hits,
+            self.highlight.as_ref(),
             |ui| {
                 if ui
                     .add(Button::new("⟳").selected(repeat_search))
@@ -222,9 +536,19 @@ impl LogViewTabTrait for FilteredLogEntriesTab {
                 {
                     repeat_search = !repeat_search;
                 };
+
+                ui.separator();
+                ui.label("Context:");
+                ui.add(
+                    egui::DragValue::new(&mut context_lines)
+                        .clamp_range(0..=100)
+                        .prefix("±"),
+                )
+                .on_hover_text("Lines Shown Before and After Each Result");
             },
         );
 
         self.repeat_search = repeat_search;
+        self.context_lines = context_lines;
     }
 }
@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use egui::{CollapsingHeader, CursorIcon, RichText, Ui};
+use egui_dock::DockState;
+use regex::Regex;
+
+use super::{
+    filtered_log_entries_tab::format_bytes,
+    log_entries_table::highlighted_text,
+    log_file_reader::LineNumber,
+    log_view::LogView,
+    search_job::SearchJob,
+    search_library::{SearchLibrary, SearchParams},
+};
+
+/// Results of the global search in one file.
+struct FileResults {
+    file_path: PathBuf,
+    results: Vec<LineNumber>,
+    search_job: Option<SearchJob>,
+    error: Option<String>,
+}
+
+/// A window running one search over every open file at once.
+pub struct GlobalSearchWindow {
+    params: SearchParams,
+    files: Vec<FileResults>,
+    highlight: Option<Regex>,
+    request_focus: bool,
+}
+
+impl GlobalSearchWindow {
+    pub fn new() -> Self {
+        Self {
+            params: Default::default(),
+            files: vec![],
+            highlight: None,
+            request_focus: true,
+        }
+    }
+
+    /// Starts searching every open file, each on its own worker thread.
+    fn execute_search(&mut self, tree: &DockState<LogView>, search_library: &mut SearchLibrary) {
+        self.files.clear();
+        self.highlight = self.params.options.highlight_regex(&self.params.term);
+        if self.params.term.is_empty() {
+            return;
+        }
+        search_library.record(self.params.clone());
+
+        for (_, log_view) in tree.iter_all_tabs() {
+            if self
+                .files
+                .iter()
+                .any(|f| f.file_path == log_view.file_path())
+            {
+                continue;
+            }
+            self.files.push(FileResults {
+                file_path: log_view.file_path().to_owned(),
+                results: vec![],
+                search_job: Some(SearchJob::start(
+                    &self.params.options,
+                    log_view.line_index(),
+                    0,
+                    &self.params.term,
+                    self.params.field.trim(),
+                )),
+                error: None,
+            });
+        }
+    }
+
+    fn poll_searches(&mut self, ctx: &egui::Context) {
+        for file in &mut self.files {
+            let Some(ref mut search_job) = file.search_job else {
+                continue;
+            };
+            search_job.poll(&mut file.results);
+            if search_job.is_finished() {
+                file.error = search_job.error().map(|e| e.to_string());
+                file.search_job = None;
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(50));
+            }
+        }
+    }
+
+    /// Shows the window. Returns false once it has been closed, along with the
+    /// file and line of the result clicked, if any.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        tree: &mut DockState<LogView>,
+        search_library: &mut SearchLibrary,
+    ) -> (bool, Option<(PathBuf, LineNumber)>) {
+        self.poll_searches(ctx);
+
+        let mut open = true;
+        let mut clicked_result = None;
+        egui::Window::new("Search All Files")
+            .open(&mut open)
+            .default_size((640.0, 420.0))
+            .show(ctx, |ui| {
+                self.ui_search_bar(ui, tree, search_library);
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        for file in &self.files {
+                            let log_view = tree
+                                .iter_all_tabs_mut()
+                                .map(|(_, log_view)| log_view)
+                                .find(|log_view| log_view.file_path() == file.file_path);
+                            if let Some(line_num) =
+                                Self::ui_file_results(ui, file, log_view, self.highlight.as_ref())
+                            {
+                                clicked_result = Some((file.file_path.clone(), line_num));
+                            }
+                        }
+                    });
+            });
+
+        (open, clicked_result)
+    }
+
+    fn ui_search_bar(
+        &mut self,
+        ui: &mut Ui,
+        tree: &DockState<LogView>,
+        search_library: &mut SearchLibrary,
+    ) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.params.term)
+                    .hint_text(if self.params.options.query {
+                        "level >= WARNING"
+                    } else {
+                        "Search text"
+                    })
+                    .desired_width(220.0),
+            );
+            if self.request_focus {
+                response.request_focus();
+                self.request_focus = false;
+            }
+            let mut search = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            let options = &mut self.params.options;
+            ui.add_enabled_ui(!options.query, |ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.params.field)
+                        .hint_text("in any field")
+                        .desired_width(100.0),
+                );
+                if ui
+                    .selectable_label(options.case_sensitive, "Case")
+                    .clicked()
+                {
+                    options.case_sensitive = !options.case_sensitive;
+                }
+                if ui.selectable_label(options.whole_word, "Word").clicked() {
+                    options.whole_word = !options.whole_word;
+                }
+                if ui.selectable_label(options.regex, "Regex").clicked() {
+                    options.regex = !options.regex;
+                }
+            });
+            if ui.selectable_label(options.query, "Query").clicked() {
+                options.query = !options.query;
+            }
+
+            search |= ui.button("Search").clicked();
+            if search {
+                self.execute_search(tree, search_library);
+            }
+        });
+
+        let total_results: usize = self.files.iter().map(|f| f.results.len()).sum();
+        let searching = self.files.iter().filter(|f| f.search_job.is_some()).count();
+        ui.horizontal(|ui| {
+            if searching > 0 {
+                let (scanned, total) = self
+                    .files
+                    .iter()
+                    .filter_map(|f| f.search_job.as_ref())
+                    .map(SearchJob::progress)
+                    .fold((0, 0), |(s, t), (scanned, total)| (s + scanned, t + total));
+                ui.spinner();
+                ui.label(format!(
+                    "Searching {} files... {} of {}, {} results",
+                    searching,
+                    format_bytes(scanned),
+                    format_bytes(total),
+                    total_results
+                ));
+            } else if !self.files.is_empty() {
+                ui.label(format!(
+                    "{} results in {} files",
+                    total_results,
+                    self.files.iter().filter(|f| !f.results.is_empty()).count()
+                ));
+            }
+        });
+    }
+
+    /// Lists the results of one file. Returns the line clicked, if any.
+    fn ui_file_results(
+        ui: &mut Ui,
+        file: &FileResults,
+        log_view: Option<&mut LogView>,
+        highlight: Option<&Regex>,
+    ) -> Option<LineNumber> {
+        let mut clicked_line = None;
+        let header = format!("{} ({})", file_name(&file.file_path), file.results.len());
+
+        CollapsingHeader::new(RichText::new(header).strong())
+            .id_source(&file.file_path)
+            .default_open(true)
+            .show(ui, |ui| {
+                if let Some(ref error) = file.error {
+                    ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                }
+                let Some(log_view) = log_view else {
+                    ui.weak("The file has been closed.");
+                    return;
+                };
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::vertical()
+                    .id_source(&file.file_path)
+                    .max_height(240.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, row_height, file.results.len(), |ui, rows| {
+                        for &line_num in &file.results[rows] {
+                            let summary = log_view.line_summary(line_num).unwrap_or_default();
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .link(RichText::new(format!("{:>7}", line_num + 1)).monospace())
+                                    .on_hover_cursor(CursorIcon::PointingHand)
+                                    .on_hover_text("Show in File")
+                                    .clicked()
+                                {
+                                    clicked_line = Some(line_num);
+                                }
+                                let text_color = ui.visuals().text_color();
+                                ui.add(
+                                    egui::Label::new(highlighted_text(
+                                        ui, &summary, text_color, highlight,
+                                    ))
+                                    .truncate(true),
+                                );
+                            });
+                        }
+                    });
+            });
+
+        clicked_line
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
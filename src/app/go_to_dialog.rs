@@ -0,0 +1,182 @@
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::Receiver;
+use egui::{Id, RichText};
+
+use super::{
+    log_analysis::BackgroundAnalysis,
+    log_file_reader::{LineIndex, LineNumber, LogFileReader},
+    time_range::{partition_lines, resolve_bound, TimestampOrder},
+    timestamp::Timestamp,
+};
+
+/// Asks for a line number or a timestamp and selects the nearest line.
+pub struct GoToDialog {
+    text: String,
+    error: Option<String>,
+    request_focus: bool,
+    /// Nearest line being searched for on a worker thread, for files out of time order.
+    pending_scan: Option<Receiver<Option<LineNumber>>>,
+}
+
+impl GoToDialog {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            error: None,
+            request_focus: true,
+            pending_scan: None,
+        }
+    }
+
+    /// Shows the dialog. Returns false once it should be closed, along with the
+    /// line to select if one was found.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        id: Id,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) -> (bool, Option<LineNumber>) {
+        let mut open = true;
+        let mut selected_line = None;
+
+        if let Some(ref pending_scan) = self.pending_scan {
+            match pending_scan.try_recv() {
+                Ok(line_num) => {
+                    self.pending_scan = None;
+                    match line_num {
+                        Some(line_num) => return (false, Some(line_num)),
+                        None => self.error = Some("No timestamped entries in the log".into()),
+                    }
+                }
+                Err(_) => ctx.request_repaint_after(Duration::from_millis(50)),
+            }
+        }
+
+        egui::Window::new("Go to…")
+            .id(id)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, (0.0, 60.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let response = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.text)
+                                .hint_text("line number or time")
+                                .desired_width(220.0),
+                        )
+                        .on_hover_text(
+                            "A line number (1234), a timestamp (2023-05-31T14:02:00Z), a time of \
+                        day (14:02), or a duration before the last entry (-15m)",
+                        );
+                    if self.request_focus {
+                        response.request_focus();
+                        self.request_focus = false;
+                    }
+
+                    let enter_pressed =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Go").clicked() || enter_pressed {
+                        match self.go_to(log_reader, timestamp_order) {
+                            Ok(line_num) => selected_line = line_num,
+                            Err(error) => self.error = Some(error),
+                        }
+                    }
+                });
+
+                if self.pending_scan.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Searching out-of-order timestamps...");
+                    });
+                } else if let Some(ref error) = self.error {
+                    ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                }
+            });
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            open = false;
+        }
+        (open && selected_line.is_none(), selected_line)
+    }
+
+    /// Finds the line to go to. Returns None if the nearest line is being searched
+    /// for in the background.
+    fn go_to(
+        &mut self,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) -> Result<Option<LineNumber>, String> {
+        self.error = None;
+        let text = self.text.trim();
+        let line_count = log_reader.line_count();
+        if line_count == 0 {
+            return Err("The log is empty".into());
+        }
+
+        if let Ok(line_num) = text.parse::<LineNumber>() {
+            return Ok(Some(line_num.clamp(1, line_count) - 1));
+        }
+
+        let (first, last) = timestamp_order.lock().bounds();
+        let Some(timestamp) = resolve_bound(text, first, last, first)? else {
+            return Err("Enter a line number or a time".into());
+        };
+
+        if timestamp_order.lock().out_of_order_lines() == 0 {
+            return Ok(Some(nearest_line_ordered(log_reader, timestamp)));
+        }
+
+        let line_index = log_reader.line_index();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        thread::spawn(move || {
+            let _ = sender.send(nearest_line_scan(&line_index, timestamp));
+        });
+        self.pending_scan = Some(receiver);
+        Ok(None)
+    }
+}
+
+/// Finds the line with the timestamp nearest to `timestamp` by binary search,
+/// assuming timestamps are in ascending order.
+fn nearest_line_ordered(log_reader: &mut LogFileReader, timestamp: Timestamp) -> LineNumber {
+    let line_count = log_reader.line_count();
+    let after = partition_lines(log_reader, 0..line_count, |t| t < timestamp);
+
+    let next = (after..line_count)
+        .find_map(|line_num| Some((line_num, log_reader.read_entry(line_num)?.time()?)));
+    let previous = (0..after)
+        .rev()
+        .find_map(|line_num| Some((line_num, log_reader.read_entry(line_num)?.time()?)));
+
+    match (previous, next) {
+        (Some((previous_line, previous_time)), Some((next_line, next_time))) => {
+            if timestamp - previous_time <= next_time - timestamp {
+                previous_line
+            } else {
+                next_line
+            }
+        }
+        (Some((line_num, _)), None) | (None, Some((line_num, _))) => line_num,
+        (None, None) => after.min(line_count - 1),
+    }
+}
+
+/// Finds the line with the timestamp nearest to `timestamp` by reading every line.
+fn nearest_line_scan(line_index: &LineIndex, timestamp: Timestamp) -> Option<LineNumber> {
+    let mut nearest: Option<(LineNumber, Timestamp)> = None;
+    let _ = line_index.for_each_line(0..line_index.line_count(), |line_num, line| {
+        if let Some(line_time) = LogFileReader::parse_logline(line).and_then(|e| e.time()) {
+            let distance = (line_time - timestamp).abs();
+            if nearest.map_or(true, |(_, nearest_distance)| distance < nearest_distance) {
+                nearest = Some((line_num, distance));
+            }
+        }
+        true
+    });
+    nearest.map(|(line_num, _)| line_num)
+}
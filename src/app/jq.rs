@@ -0,0 +1,1164 @@
+//! A subset of the jq language for filtering and reshaping log entries.
+//!
+//! ```text
+//! select(.level == "ERROR" and .latency > 1000) | {t, tag, msg: .message}
+//! .http.request | select(.method != "GET") | [.method, .path]
+//! ```
+//!
+//! * Paths: `.`, `.field`, `.a.b`, `.["key"]`, `.[0]`, `.[]`.
+//! * Pipes `|` and multiple outputs `,`; the alternative operator `//`.
+//! * Comparisons `==`, `!=`, `<`, `<=`, `>`, `>=`, with `and`, `or` and `not`.
+//! * Arithmetic `+`, `-`, `*`, `/`, `%`.
+//! * Literals, arrays `[...]` and objects `{a, b: .c, "d": 1, (.key): .value}`.
+//!   Object values containing a pipe must be parenthesized.
+//! * Functions: `select(f)`, `map(f)`, `empty`, `not`, `length`, `keys`, `has(k)`,
+//!   `contains(x)`, `startswith(s)`, `endswith(s)`, `test("regex")`, `test("regex"; "i")`,
+//!   `ascii_downcase`, `ascii_upcase`, `tostring`, `tonumber`, `type`.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use json::{object::Object, JsonValue};
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug)]
+pub struct JqError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl Display for JqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Alternative,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Dot,
+    Field(String),
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Pipe,
+    Comma,
+    Colon,
+    Semicolon,
+    Op(BinaryOp),
+    Ident(String),
+    Text(String),
+    Number(f64),
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Builtin {
+    Select,
+    Map,
+    Empty,
+    Not,
+    Length,
+    Keys,
+    Has,
+    Contains,
+    StartsWith,
+    EndsWith,
+    AsciiDowncase,
+    AsciiUpcase,
+    ToString,
+    ToNumber,
+    Type,
+}
+
+impl Builtin {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "select" => Builtin::Select,
+            "map" => Builtin::Map,
+            "empty" => Builtin::Empty,
+            "not" => Builtin::Not,
+            "length" => Builtin::Length,
+            "keys" => Builtin::Keys,
+            "has" => Builtin::Has,
+            "contains" => Builtin::Contains,
+            "startswith" => Builtin::StartsWith,
+            "endswith" => Builtin::EndsWith,
+            "ascii_downcase" => Builtin::AsciiDowncase,
+            "ascii_upcase" => Builtin::AsciiUpcase,
+            "tostring" => Builtin::ToString,
+            "tonumber" => Builtin::ToNumber,
+            "type" => Builtin::Type,
+            _ => return None,
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Builtin::Select
+            | Builtin::Map
+            | Builtin::Has
+            | Builtin::Contains
+            | Builtin::StartsWith
+            | Builtin::EndsWith => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Identity,
+    Literal(JsonValue),
+    /// Indexes the outputs of the first filter with the outputs of the second,
+    /// which is evaluated against the original input.
+    Index(Box<Filter>, Box<Filter>),
+    Iterate(Box<Filter>),
+    Pipe(Box<Filter>, Box<Filter>),
+    Comma(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Binary(BinaryOp, Box<Filter>, Box<Filter>),
+    Negate(Box<Filter>),
+    Array(Option<Box<Filter>>),
+    /// Key and value filters of each entry.
+    Object(Vec<(Filter, Filter)>),
+    Call(Builtin, Vec<Filter>),
+    Test(Regex),
+}
+
+/// A parsed jq expression that can be evaluated against JSON values.
+#[derive(Clone, Debug)]
+pub struct JqFilter {
+    filter: Filter,
+}
+
+impl JqFilter {
+    pub fn parse(text: &str) -> Result<Self, JqError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end_position: text.len(),
+        };
+        let filter = parser.parse_pipe()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(JqError {
+                message: "Unexpected input".to_string(),
+                position: token.position,
+            });
+        }
+        Ok(Self { filter })
+    }
+
+    /// Runs the filter on `input`, returning its outputs. A filter can output
+    /// any number of values, e.g. none if `select` rejects the input.
+    pub fn evaluate(&self, input: &JsonValue) -> Result<Vec<JsonValue>, String> {
+        let mut outputs = vec![];
+        self.filter.evaluate(input, &mut outputs)?;
+        Ok(outputs)
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Boolean(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::Short(_) | JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn is_truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::Boolean(false))
+}
+
+/// Orders values like jq: null < false < true < numbers < strings < arrays < objects.
+fn compare_values(lhs: &JsonValue, rhs: &JsonValue) -> Ordering {
+    fn rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Null => 0,
+            JsonValue::Boolean(false) => 1,
+            JsonValue::Boolean(true) => 2,
+            JsonValue::Number(_) => 3,
+            JsonValue::Short(_) | JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+        }
+    }
+
+    match (lhs, rhs) {
+        (JsonValue::Number(_), JsonValue::Number(_)) => {
+            let (lhs, rhs) = (lhs.as_f64().unwrap_or(0.0), rhs.as_f64().unwrap_or(0.0));
+            lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal)
+        }
+        (JsonValue::Array(lhs), JsonValue::Array(rhs)) => lhs
+            .iter()
+            .zip(rhs)
+            .map(|(l, r)| compare_values(l, r))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| lhs.len().cmp(&rhs.len())),
+        (JsonValue::Object(_), JsonValue::Object(_)) => lhs.dump().cmp(&rhs.dump()),
+        _ if rank(lhs) == 4 && rank(rhs) == 4 => lhs.as_str().cmp(&rhs.as_str()),
+        _ => rank(lhs).cmp(&rank(rhs)),
+    }
+}
+
+fn index(target: &JsonValue, index: &JsonValue) -> Result<JsonValue, String> {
+    match (target, index) {
+        (JsonValue::Null, _) => Ok(JsonValue::Null),
+        (JsonValue::Object(object), key) if key.is_string() => Ok(object
+            .get(key.as_str().unwrap_or_default())
+            .cloned()
+            .unwrap_or(JsonValue::Null)),
+        (JsonValue::Array(array), JsonValue::Number(_)) => {
+            let index = index.as_f64().unwrap_or(0.0).floor() as i64;
+            let index = if index < 0 {
+                array.len() as i64 + index
+            } else {
+                index
+            };
+            Ok(usize::try_from(index)
+                .ok()
+                .and_then(|index| array.get(index))
+                .cloned()
+                .unwrap_or(JsonValue::Null))
+        }
+        _ => Err(format!(
+            "Cannot index {} with {}",
+            type_name(target),
+            type_name(index)
+        )),
+    }
+}
+
+fn iterate(value: &JsonValue, outputs: &mut Vec<JsonValue>) -> Result<(), String> {
+    match value {
+        JsonValue::Array(array) => outputs.extend(array.iter().cloned()),
+        JsonValue::Object(object) => outputs.extend(object.iter().map(|(_, v)| v.clone())),
+        _ => return Err(format!("Cannot iterate over {}", type_name(value))),
+    }
+    Ok(())
+}
+
+fn arithmetic(op: BinaryOp, lhs: &JsonValue, rhs: &JsonValue) -> Result<JsonValue, String> {
+    let error = || {
+        format!(
+            "Cannot apply {:?} to {} and {}",
+            op,
+            type_name(lhs),
+            type_name(rhs)
+        )
+    };
+
+    match (op, lhs, rhs) {
+        (BinaryOp::Add, JsonValue::Null, value) | (BinaryOp::Add, value, JsonValue::Null) => {
+            Ok(value.clone())
+        }
+        (_, JsonValue::Number(_), JsonValue::Number(_)) => {
+            let (lhs, rhs) = (lhs.as_f64().unwrap_or(0.0), rhs.as_f64().unwrap_or(0.0));
+            let result = match op {
+                BinaryOp::Add => lhs + rhs,
+                BinaryOp::Subtract => lhs - rhs,
+                BinaryOp::Multiply => lhs * rhs,
+                BinaryOp::Divide if rhs == 0.0 => return Err("Division by zero".to_string()),
+                BinaryOp::Divide => lhs / rhs,
+                BinaryOp::Modulo => match (lhs as i64).checked_rem(rhs as i64) {
+                    Some(remainder) => remainder as f64,
+                    None if rhs as i64 == 0 => return Err("Modulo by zero".to_string()),
+                    None => return Err(format!("{} % {} is out of range", lhs, rhs)),
+                },
+                _ => return Err(error()),
+            };
+            Ok(result.into())
+        }
+        (BinaryOp::Add, lhs, rhs) if lhs.is_string() && rhs.is_string() => Ok(format!(
+            "{}{}",
+            lhs.as_str().unwrap_or_default(),
+            rhs.as_str().unwrap_or_default()
+        )
+        .into()),
+        (BinaryOp::Add, JsonValue::Array(lhs), JsonValue::Array(rhs)) => {
+            Ok(JsonValue::Array(lhs.iter().chain(rhs).cloned().collect()))
+        }
+        (BinaryOp::Subtract, JsonValue::Array(lhs), JsonValue::Array(rhs)) => Ok(JsonValue::Array(
+            lhs.iter()
+                .filter(|value| !rhs.contains(value))
+                .cloned()
+                .collect(),
+        )),
+        (BinaryOp::Add, JsonValue::Object(lhs), JsonValue::Object(rhs)) => {
+            let mut merged = lhs.clone();
+            for (key, value) in rhs.iter() {
+                merged.insert(key, value.clone());
+            }
+            Ok(JsonValue::Object(merged))
+        }
+        _ => Err(error()),
+    }
+}
+
+/// True if `value` contains `part`, with jq's `contains` semantics.
+fn contains(value: &JsonValue, part: &JsonValue) -> Result<bool, String> {
+    match (value, part) {
+        (JsonValue::Object(value), JsonValue::Object(part)) => {
+            for (key, part_value) in part.iter() {
+                match value.get(key) {
+                    Some(value) if contains(value, part_value)? => {}
+                    _ => return Ok(false),
+                }
+            }
+            Ok(true)
+        }
+        (JsonValue::Array(values), JsonValue::Array(parts)) => {
+            for part in parts {
+                let mut found = false;
+                for value in values {
+                    found |= contains(value, part).unwrap_or(false);
+                }
+                if !found {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        _ if value.is_string() && part.is_string() => Ok(value
+            .as_str()
+            .unwrap_or_default()
+            .contains(part.as_str().unwrap_or_default())),
+        _ if type_name(value) == type_name(part) => Ok(value == part),
+        _ => Err(format!(
+            "Cannot check whether {} contains {}",
+            type_name(value),
+            type_name(part)
+        )),
+    }
+}
+
+fn string_input<'a>(value: &'a JsonValue, function: &str) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("{} requires a string, not {}", function, type_name(value)))
+}
+
+impl Filter {
+    /// Outputs of the filter for one input, collected into a new vector.
+    fn outputs(&self, input: &JsonValue) -> Result<Vec<JsonValue>, String> {
+        let mut outputs = vec![];
+        self.evaluate(input, &mut outputs)?;
+        Ok(outputs)
+    }
+
+    fn evaluate(&self, input: &JsonValue, outputs: &mut Vec<JsonValue>) -> Result<(), String> {
+        match self {
+            Filter::Identity => outputs.push(input.clone()),
+            Filter::Literal(value) => outputs.push(value.clone()),
+            Filter::Index(target, key) => {
+                let keys = key.outputs(input)?;
+                for target in target.outputs(input)? {
+                    for key in &keys {
+                        outputs.push(index(&target, key)?);
+                    }
+                }
+            }
+            Filter::Iterate(target) => {
+                for target in target.outputs(input)? {
+                    iterate(&target, outputs)?;
+                }
+            }
+            Filter::Pipe(lhs, rhs) => {
+                for value in lhs.outputs(input)? {
+                    rhs.evaluate(&value, outputs)?;
+                }
+            }
+            Filter::Comma(lhs, rhs) => {
+                lhs.evaluate(input, outputs)?;
+                rhs.evaluate(input, outputs)?;
+            }
+            Filter::And(lhs, rhs) | Filter::Or(lhs, rhs) => {
+                let is_and = matches!(self, Filter::And(..));
+                for lhs in lhs.outputs(input)? {
+                    if is_truthy(&lhs) != is_and {
+                        outputs.push((!is_and).into());
+                        continue;
+                    }
+                    for rhs in rhs.outputs(input)? {
+                        outputs.push(is_truthy(&rhs).into());
+                    }
+                }
+            }
+            Filter::Binary(BinaryOp::Alternative, lhs, rhs) => {
+                let values: Vec<JsonValue> = lhs
+                    .outputs(input)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(is_truthy)
+                    .collect();
+                if values.is_empty() {
+                    rhs.evaluate(input, outputs)?;
+                } else {
+                    outputs.extend(values);
+                }
+            }
+            Filter::Binary(op, lhs, rhs) => {
+                let rhs_values = rhs.outputs(input)?;
+                for lhs in lhs.outputs(input)? {
+                    for rhs in &rhs_values {
+                        let ordering = || compare_values(&lhs, rhs);
+                        let result = match op {
+                            BinaryOp::Eq => ordering().is_eq().into(),
+                            BinaryOp::Ne => ordering().is_ne().into(),
+                            BinaryOp::Lt => ordering().is_lt().into(),
+                            BinaryOp::Le => ordering().is_le().into(),
+                            BinaryOp::Gt => ordering().is_gt().into(),
+                            BinaryOp::Ge => ordering().is_ge().into(),
+                            _ => arithmetic(*op, &lhs, rhs)?,
+                        };
+                        outputs.push(result);
+                    }
+                }
+            }
+            Filter::Negate(filter) => {
+                for value in filter.outputs(input)? {
+                    match value.as_f64() {
+                        Some(number) => outputs.push((-number).into()),
+                        None => return Err(format!("Cannot negate {}", type_name(&value))),
+                    }
+                }
+            }
+            Filter::Array(filter) => {
+                let values = match filter {
+                    Some(filter) => filter.outputs(input)?,
+                    None => vec![],
+                };
+                outputs.push(JsonValue::Array(values));
+            }
+            Filter::Object(entries) => {
+                let mut objects = vec![Object::new()];
+                for (key, value) in entries {
+                    let keys = key.outputs(input)?;
+                    let values = value.outputs(input)?;
+                    let mut extended = vec![];
+                    for object in &objects {
+                        for key in &keys {
+                            let Some(key) = key.as_str() else {
+                                return Err(format!(
+                                    "Object keys must be strings, not {}",
+                                    type_name(key)
+                                ));
+                            };
+                            for value in &values {
+                                let mut object = object.clone();
+                                object.insert(key, value.clone());
+                                extended.push(object);
+                            }
+                        }
+                    }
+                    objects = extended;
+                }
+                outputs.extend(objects.into_iter().map(JsonValue::Object));
+            }
+            Filter::Test(regex) => {
+                outputs.push(regex.is_match(string_input(input, "test")?).into());
+            }
+            Filter::Call(builtin, args) => Self::call(*builtin, args, input, outputs)?,
+        }
+        Ok(())
+    }
+
+    fn call(
+        builtin: Builtin,
+        args: &[Filter],
+        input: &JsonValue,
+        outputs: &mut Vec<JsonValue>,
+    ) -> Result<(), String> {
+        match builtin {
+            Builtin::Select => {
+                for condition in args[0].outputs(input)? {
+                    if is_truthy(&condition) {
+                        outputs.push(input.clone());
+                    }
+                }
+            }
+            Builtin::Map => {
+                let mut elements = vec![];
+                iterate(input, &mut elements)?;
+                let mut mapped = vec![];
+                for element in &elements {
+                    args[0].evaluate(element, &mut mapped)?;
+                }
+                outputs.push(JsonValue::Array(mapped));
+            }
+            Builtin::Empty => {}
+            Builtin::Not => outputs.push((!is_truthy(input)).into()),
+            Builtin::Length => outputs.push(match input {
+                JsonValue::Null => 0.into(),
+                JsonValue::Boolean(_) => return Err("boolean has no length".to_string()),
+                JsonValue::Number(_) => input.as_f64().unwrap_or(0.0).abs().into(),
+                JsonValue::Short(_) | JsonValue::String(_) => {
+                    input.as_str().unwrap_or_default().chars().count().into()
+                }
+                JsonValue::Array(array) => array.len().into(),
+                JsonValue::Object(object) => object.len().into(),
+            }),
+            Builtin::Keys => match input {
+                JsonValue::Object(object) => {
+                    let mut keys: Vec<&str> = object.iter().map(|(key, _)| key).collect();
+                    keys.sort_unstable();
+                    outputs.push(JsonValue::Array(keys.into_iter().map(Into::into).collect()));
+                }
+                JsonValue::Array(array) => {
+                    outputs.push(JsonValue::Array((0..array.len()).map(Into::into).collect()));
+                }
+                _ => return Err(format!("{} has no keys", type_name(input))),
+            },
+            Builtin::Has => {
+                for key in args[0].outputs(input)? {
+                    let has = match (input, &key) {
+                        (JsonValue::Object(object), key) if key.is_string() => {
+                            object.get(key.as_str().unwrap_or_default()).is_some()
+                        }
+                        (JsonValue::Array(array), JsonValue::Number(_)) => key
+                            .as_f64()
+                            .is_some_and(|index| index >= 0.0 && (index as usize) < array.len()),
+                        _ => {
+                            return Err(format!(
+                                "Cannot check whether {} has a {} key",
+                                type_name(input),
+                                type_name(&key)
+                            ))
+                        }
+                    };
+                    outputs.push(has.into());
+                }
+            }
+            Builtin::Contains => {
+                for part in args[0].outputs(input)? {
+                    outputs.push(contains(input, &part)?.into());
+                }
+            }
+            Builtin::StartsWith | Builtin::EndsWith => {
+                let name = if builtin == Builtin::StartsWith {
+                    "startswith"
+                } else {
+                    "endswith"
+                };
+                let text = string_input(input, name)?;
+                for affix in args[0].outputs(input)? {
+                    let affix = string_input(&affix, name)?;
+                    outputs.push(match builtin {
+                        Builtin::StartsWith => text.starts_with(affix).into(),
+                        _ => text.ends_with(affix).into(),
+                    });
+                }
+            }
+            Builtin::AsciiDowncase => outputs.push(
+                string_input(input, "ascii_downcase")?
+                    .to_ascii_lowercase()
+                    .into(),
+            ),
+            Builtin::AsciiUpcase => outputs.push(
+                string_input(input, "ascii_upcase")?
+                    .to_ascii_uppercase()
+                    .into(),
+            ),
+            Builtin::ToString => outputs.push(match input.as_str() {
+                Some(text) => text.into(),
+                None => input.dump().into(),
+            }),
+            Builtin::ToNumber => {
+                let number = match input {
+                    JsonValue::Number(_) => input.as_f64(),
+                    _ => input.as_str().and_then(|text| text.trim().parse().ok()),
+                };
+                match number {
+                    Some(number) => outputs.push(number.into()),
+                    None => return Err(format!("Cannot parse {} as a number", input.dump())),
+                }
+            }
+            Builtin::Type => outputs.push(type_name(input).into()),
+        }
+        Ok(())
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, JqError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '.' => match chars.peek() {
+                Some(&(_, next)) if is_ident_start(next) => {
+                    let mut name = String::new();
+                    while let Some((_, c)) = chars.next_if(|(_, c)| is_ident_char(*c)) {
+                        name.push(c);
+                    }
+                    TokenKind::Field(name)
+                }
+                _ => TokenKind::Dot,
+            },
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
+            '{' => TokenKind::LBrace,
+            '}' => TokenKind::RBrace,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '|' => TokenKind::Pipe,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            ';' => TokenKind::Semicolon,
+            '+' => TokenKind::Op(BinaryOp::Add),
+            '-' => TokenKind::Op(BinaryOp::Subtract),
+            '*' => TokenKind::Op(BinaryOp::Multiply),
+            '%' => TokenKind::Op(BinaryOp::Modulo),
+            '/' if chars.next_if(|(_, c)| *c == '/').is_some() => {
+                TokenKind::Op(BinaryOp::Alternative)
+            }
+            '/' => TokenKind::Op(BinaryOp::Divide),
+            '=' if chars.next_if(|(_, c)| *c == '=').is_some() => TokenKind::Op(BinaryOp::Eq),
+            '!' if chars.next_if(|(_, c)| *c == '=').is_some() => TokenKind::Op(BinaryOp::Ne),
+            '<' if chars.next_if(|(_, c)| *c == '=').is_some() => TokenKind::Op(BinaryOp::Le),
+            '<' => TokenKind::Op(BinaryOp::Lt),
+            '>' if chars.next_if(|(_, c)| *c == '=').is_some() => TokenKind::Op(BinaryOp::Ge),
+            '>' => TokenKind::Op(BinaryOp::Gt),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, 'r')) => value.push('\r'),
+                            Some((_, escaped)) => value.push(escaped),
+                            None => break,
+                        },
+                        Some((_, '"')) => break,
+                        Some((_, other)) => value.push(other),
+                        None => {
+                            return Err(JqError {
+                                message: "Unterminated string".to_string(),
+                                position,
+                            })
+                        }
+                    }
+                }
+                TokenKind::Text(value)
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                TokenKind::Number(number.parse().map_err(|_| JqError {
+                    message: format!("Invalid number '{}'", number),
+                    position,
+                })?)
+            }
+            c if is_ident_start(c) => {
+                let mut name = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_ident_char(*c)) {
+                    name.push(c);
+                }
+                TokenKind::Ident(name)
+            }
+            _ => {
+                return Err(JqError {
+                    message: format!("Unexpected character '{}'", c),
+                    position,
+                })
+            }
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    end_position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|token| &token.kind)
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        let matched = self.peek_kind() == Some(kind);
+        if matched {
+            self.next += 1;
+        }
+        matched
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = matches!(self.peek_kind(), Some(TokenKind::Ident(name)) if name == keyword);
+        if matched {
+            self.next += 1;
+        }
+        matched
+    }
+
+    fn advance(&mut self) -> Result<Token, JqError> {
+        let token = self.tokens.get(self.next).cloned().ok_or(JqError {
+            message: "Unexpected end of expression".to_string(),
+            position: self.end_position,
+        })?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, kind: TokenKind, description: &str) -> Result<(), JqError> {
+        let token = self.advance()?;
+        if token.kind == kind {
+            Ok(())
+        } else {
+            Err(JqError {
+                message: format!("Expected {}", description),
+                position: token.position,
+            })
+        }
+    }
+
+    fn parse_pipe(&mut self) -> Result<Filter, JqError> {
+        let lhs = self.parse_comma()?;
+        if self.eat(&TokenKind::Pipe) {
+            return Ok(Filter::Pipe(Box::new(lhs), Box::new(self.parse_pipe()?)));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comma(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_alternative()?;
+        while self.eat(&TokenKind::Comma) {
+            filter = Filter::Comma(Box::new(filter), Box::new(self.parse_alternative()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_alternative(&mut self) -> Result<Filter, JqError> {
+        let lhs = self.parse_or()?;
+        if self.eat(&TokenKind::Op(BinaryOp::Alternative)) {
+            let rhs = self.parse_alternative()?;
+            return Ok(Filter::Binary(
+                BinaryOp::Alternative,
+                Box::new(lhs),
+                Box::new(rhs),
+            ));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_and()?;
+        while self.eat_keyword("or") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_comparison()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Filter, JqError> {
+        let lhs = self.parse_additive()?;
+        let op = match self.peek_kind() {
+            Some(TokenKind::Op(
+                op @ (BinaryOp::Eq
+                | BinaryOp::Ne
+                | BinaryOp::Lt
+                | BinaryOp::Le
+                | BinaryOp::Gt
+                | BinaryOp::Ge),
+            )) => *op,
+            _ => return Ok(lhs),
+        };
+        self.next += 1;
+        let rhs = self.parse_additive()?;
+        Ok(Filter::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_multiplicative()?;
+        while let Some(TokenKind::Op(op @ (BinaryOp::Add | BinaryOp::Subtract))) = self.peek_kind()
+        {
+            let op = *op;
+            self.next += 1;
+            let rhs = self.parse_multiplicative()?;
+            filter = Filter::Binary(op, Box::new(filter), Box::new(rhs));
+        }
+        Ok(filter)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_unary()?;
+        while let Some(TokenKind::Op(
+            op @ (BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo),
+        )) = self.peek_kind()
+        {
+            let op = *op;
+            self.next += 1;
+            let rhs = self.parse_unary()?;
+            filter = Filter::Binary(op, Box::new(filter), Box::new(rhs));
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, JqError> {
+        if self.eat(&TokenKind::Op(BinaryOp::Subtract)) {
+            return Ok(Filter::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Filter, JqError> {
+        let mut filter = self.parse_primary()?;
+        loop {
+            match self.peek_kind() {
+                Some(TokenKind::Field(name)) => {
+                    let key = Filter::Literal(name.as_str().into());
+                    self.next += 1;
+                    filter = Filter::Index(Box::new(filter), Box::new(key));
+                }
+                Some(TokenKind::LBracket) => {
+                    self.next += 1;
+                    filter = self.parse_brackets(filter)?;
+                }
+                Some(TokenKind::Dot)
+                    if self.tokens.get(self.next + 1).map(|t| &t.kind)
+                        == Some(&TokenKind::LBracket) =>
+                {
+                    self.next += 2;
+                    filter = self.parse_brackets(filter)?;
+                }
+                _ => return Ok(filter),
+            }
+        }
+    }
+
+    /// Parses `[]` or `[index]` after a filter, the opening bracket already consumed.
+    fn parse_brackets(&mut self, target: Filter) -> Result<Filter, JqError> {
+        if self.eat(&TokenKind::RBracket) {
+            return Ok(Filter::Iterate(Box::new(target)));
+        }
+        let index = self.parse_pipe()?;
+        self.expect(TokenKind::RBracket, "']'")?;
+        Ok(Filter::Index(Box::new(target), Box::new(index)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Filter, JqError> {
+        let token = self.advance()?;
+        match token.kind {
+            TokenKind::Dot => Ok(Filter::Identity),
+            TokenKind::Field(name) => Ok(Filter::Index(
+                Box::new(Filter::Identity),
+                Box::new(Filter::Literal(name.into())),
+            )),
+            TokenKind::Text(text) => Ok(Filter::Literal(text.into())),
+            TokenKind::Number(number) => Ok(Filter::Literal(number.into())),
+            TokenKind::LParen => {
+                let filter = self.parse_pipe()?;
+                self.expect(TokenKind::RParen, "')'")?;
+                Ok(filter)
+            }
+            TokenKind::LBracket => {
+                if self.eat(&TokenKind::RBracket) {
+                    return Ok(Filter::Array(None));
+                }
+                let filter = self.parse_pipe()?;
+                self.expect(TokenKind::RBracket, "']'")?;
+                Ok(Filter::Array(Some(Box::new(filter))))
+            }
+            TokenKind::LBrace => self.parse_object(),
+            TokenKind::Ident(name) => self.parse_identifier(name, token.position),
+            _ => Err(JqError {
+                message: "Expected a filter".to_string(),
+                position: token.position,
+            }),
+        }
+    }
+
+    fn parse_identifier(&mut self, name: String, position: usize) -> Result<Filter, JqError> {
+        match name.as_str() {
+            "true" => return Ok(Filter::Literal(true.into())),
+            "false" => return Ok(Filter::Literal(false.into())),
+            "null" => return Ok(Filter::Literal(JsonValue::Null)),
+            _ => {}
+        }
+
+        let mut args = vec![];
+        if self.eat(&TokenKind::LParen) {
+            loop {
+                args.push(self.parse_pipe()?);
+                if !self.eat(&TokenKind::Semicolon) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::RParen, "')'")?;
+        }
+
+        if name == "test" {
+            return Self::parse_test(args, position);
+        }
+
+        let Some(builtin) = Builtin::from_name(&name) else {
+            return Err(JqError {
+                message: format!("Unknown function '{}'", name),
+                position,
+            });
+        };
+        if args.len() != builtin.arity() {
+            return Err(JqError {
+                message: format!("{} takes {} arguments", name, builtin.arity()),
+                position,
+            });
+        }
+        Ok(Filter::Call(builtin, args))
+    }
+
+    /// `test` only accepts literal patterns and flags, so the regex is compiled once.
+    fn parse_test(args: Vec<Filter>, position: usize) -> Result<Filter, JqError> {
+        let literal = |filter: &Filter| match filter {
+            Filter::Literal(value) => value.as_str().map(str::to_string),
+            _ => None,
+        };
+        let pattern = args.first().and_then(literal);
+        let flags = args.get(1).map(literal);
+        let (Some(pattern), None | Some(Some(_))) = (pattern, &flags) else {
+            return Err(JqError {
+                message: "test takes a string pattern and optional string flags".to_string(),
+                position,
+            });
+        };
+        if args.len() > 2 {
+            return Err(JqError {
+                message: "test takes at most 2 arguments".to_string(),
+                position,
+            });
+        }
+
+        let case_insensitive = flags.flatten().is_some_and(|flags| flags.contains('i'));
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| JqError {
+                message: format!("Invalid regex: {}", e),
+                position,
+            })?;
+        Ok(Filter::Test(regex))
+    }
+
+    fn parse_object(&mut self) -> Result<Filter, JqError> {
+        let mut entries = vec![];
+        if self.eat(&TokenKind::RBrace) {
+            return Ok(Filter::Object(entries));
+        }
+
+        loop {
+            let token = self.advance()?;
+            let (key, shorthand_name) = match token.kind {
+                TokenKind::Ident(name) | TokenKind::Text(name) => {
+                    (Filter::Literal(name.as_str().into()), Some(name))
+                }
+                TokenKind::LParen => {
+                    let key = self.parse_pipe()?;
+                    self.expect(TokenKind::RParen, "')'")?;
+                    (key, None)
+                }
+                _ => {
+                    return Err(JqError {
+                        message: "Expected an object key".to_string(),
+                        position: token.position,
+                    })
+                }
+            };
+
+            let value = if self.eat(&TokenKind::Colon) {
+                self.parse_alternative()?
+            } else if let Some(name) = shorthand_name {
+                Filter::Index(
+                    Box::new(Filter::Identity),
+                    Box::new(Filter::Literal(name.into())),
+                )
+            } else {
+                return Err(JqError {
+                    message: "Expected ':' after a computed key".to_string(),
+                    position: token.position,
+                });
+            };
+            entries.push((key, value));
+
+            if self.eat(&TokenKind::RBrace) {
+                return Ok(Filter::Object(entries));
+            }
+            self.expect(TokenKind::Comma, "',' or '}'")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates `expression` on `input`, returning the outputs as compact JSON.
+    fn eval(expression: &str, input: &str) -> Result<Vec<String>, String> {
+        let filter = JqFilter::parse(expression).map_err(|e| e.to_string())?;
+        let outputs = filter.evaluate(&json::parse(input).unwrap())?;
+        Ok(outputs.iter().map(|output| output.dump()).collect())
+    }
+
+    const ENTRY: &str = r#"{"t": "2023-06-25T00:49:20Z", "level": "ERROR", "latency": 1500,
+        "message": "disk full", "tags": ["db", "io"], "http": {"method": "POST", "status": 500}}"#;
+
+    #[test]
+    fn paths() {
+        assert_eq!(eval(".level", ENTRY).unwrap(), [r#""ERROR""#]);
+        assert_eq!(eval(".http.method", ENTRY).unwrap(), [r#""POST""#]);
+        assert_eq!(eval(r#".["http"].status"#, ENTRY).unwrap(), ["500"]);
+        assert_eq!(eval(".tags[1]", ENTRY).unwrap(), [r#""io""#]);
+    }
+
+    #[test]
+    fn missing_fields_are_null() {
+        assert_eq!(eval(".missing", ENTRY).unwrap(), ["null"]);
+        assert_eq!(eval(".missing.nested", ENTRY).unwrap(), ["null"]);
+        assert_eq!(eval(".tags[5]", ENTRY).unwrap(), ["null"]);
+        assert_eq!(
+            eval(r#".missing // "default""#, ENTRY).unwrap(),
+            [r#""default""#]
+        );
+        assert!(eval("select(.missing == 1)", ENTRY).unwrap().is_empty());
+    }
+
+    #[test]
+    fn multiple_outputs() {
+        assert_eq!(eval(".tags[]", ENTRY).unwrap(), [r#""db""#, r#""io""#]);
+        assert_eq!(eval(".http | .[]", ENTRY).unwrap(), [r#""POST""#, "500"]);
+        assert_eq!(
+            eval(".level, .message", ENTRY).unwrap(),
+            [r#""ERROR""#, r#""disk full""#]
+        );
+        assert_eq!(
+            eval("{tag: .tags[]}", ENTRY).unwrap(),
+            [r#"{"tag":"db"}"#, r#"{"tag":"io"}"#]
+        );
+        assert!(eval("empty", ENTRY).unwrap().is_empty());
+    }
+
+    #[test]
+    fn comparisons_and_logic() {
+        let select = r#"select(.level == "ERROR" and .latency > 1000) | .message"#;
+        assert_eq!(eval(select, ENTRY).unwrap(), [r#""disk full""#]);
+        assert!(eval(r#"select(.http.method != "POST")"#, ENTRY)
+            .unwrap()
+            .is_empty());
+        assert_eq!(eval(".latency >= 1500", ENTRY).unwrap(), ["true"]);
+        assert_eq!(
+            eval(".latency < 1500 or .level == \"INFO\"", ENTRY).unwrap(),
+            ["false"]
+        );
+        assert_eq!(eval(".latency <= 1500 | not", ENTRY).unwrap(), ["false"]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval(".latency / 1000", ENTRY).unwrap(), ["1.5"]);
+        assert_eq!(eval(".latency % 7 + 1", ENTRY).unwrap(), ["3"]);
+        assert_eq!(eval(".http.status - 2 * 50", ENTRY).unwrap(), ["400"]);
+        assert_eq!(
+            eval(r#".message + "!""#, ENTRY).unwrap(),
+            [r#""disk full!""#]
+        );
+    }
+
+    #[test]
+    fn construction_and_functions() {
+        assert_eq!(
+            eval("{level, msg: .message}", ENTRY).unwrap(),
+            [r#"{"level":"ERROR","msg":"disk full"}"#]
+        );
+        assert_eq!(
+            eval("[.tags[] | ascii_upcase]", ENTRY).unwrap(),
+            [r#"["DB","IO"]"#]
+        );
+        assert_eq!(eval(".tags | length", ENTRY).unwrap(), ["2"]);
+        assert_eq!(
+            eval(".http | keys", ENTRY).unwrap(),
+            [r#"["method","status"]"#]
+        );
+        assert_eq!(
+            eval(r#".message | test("DISK"; "i")"#, ENTRY).unwrap(),
+            ["true"]
+        );
+        assert_eq!(eval(r#"has("http")"#, ENTRY).unwrap(), ["true"]);
+        assert_eq!(
+            eval(".latency | tostring | type", ENTRY).unwrap(),
+            [r#""string""#]
+        );
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        assert!(eval(".message.field", ENTRY).is_err());
+        assert!(eval(".message[]", ENTRY).is_err());
+        assert!(eval(".latency % 0", ENTRY).is_err());
+        assert!(eval(&format!("{} % -1", i64::MIN), ENTRY).is_err());
+        assert!(eval(".tags + 1", ENTRY).is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(JqFilter::parse(".a |").is_err());
+        assert!(JqFilter::parse("select(.a").is_err());
+        assert!(JqFilter::parse("unknown_function").is_err());
+        assert!(JqFilter::parse(r#"test("(")"#).is_err());
+        assert_eq!(JqFilter::parse(".a )").unwrap_err().position, 3);
+    }
+}
@@ -0,0 +1,375 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, SystemTime};
+
+use egui::{Align, RichText, Ui};
+use egui_extras::{Column, TableBuilder};
+use json::JsonValue;
+use log::error;
+
+use super::{
+    filtered_log_entries_tab::format_bytes,
+    jq::JqFilter,
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+    search_job::SearchJob,
+};
+
+/// Number of results whose outputs are sampled to choose the table columns.
+const COLUMN_SAMPLE_LEN: usize = 50;
+
+/// Column showing outputs that aren't objects.
+const VALUE_COLUMN: &str = "value";
+
+/// Runtime errors raised by the expression, e.g. indexing a string, counted by the worker.
+#[derive(Default)]
+struct EvaluationErrors {
+    count: AtomicUsize,
+    first: Mutex<Option<(LineNumber, String)>>,
+}
+
+/// One output of the expression, shown as a table row.
+struct JqRow {
+    line_num: LineNumber,
+    /// Position of the output among those produced by the line.
+    output_index: usize,
+    value: JsonValue,
+}
+
+/// Evaluates a jq expression over every log entry and shows the outputs in a table.
+/// Rows link back to the line that produced them.
+pub struct JqTab {
+    expression: String,
+    filter: Option<Arc<JqFilter>>,
+    parse_error: Option<String>,
+    evaluation_errors: Arc<EvaluationErrors>,
+    /// Outputs of the expression, ordered by line.
+    results: Vec<JqRow>,
+    /// Results of a re-run after the file changed, shown once the run completes.
+    pending_results: Option<Vec<JqRow>>,
+    /// Rows evaluated by the worker since the last poll.
+    new_rows: Arc<Mutex<Vec<JqRow>>>,
+    job: Option<SearchJob>,
+    /// Keys of the projected objects, chosen from the first results.
+    columns: Vec<String>,
+    /// Number of results the columns were chosen from.
+    columns_sampled: usize,
+    expression_changed: bool,
+    last_run_time: Option<SystemTime>,
+    scrolled_to_line: Option<LineNumber>,
+}
+
+impl JqTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            expression: String::new(),
+            filter: None,
+            parse_error: None,
+            evaluation_errors: Default::default(),
+            results: vec![],
+            pending_results: None,
+            new_rows: Default::default(),
+            job: None,
+            columns: vec![],
+            columns_sampled: 0,
+            expression_changed: false,
+            last_run_time: None,
+            scrolled_to_line: None,
+        })
+    }
+
+    /// Parses the expression and starts evaluating it on a worker thread.
+    /// A re-run keeps showing the current results until it finishes.
+    fn execute(&mut self, log_reader: &LogFileReader, repeat: bool) {
+        self.last_run_time = Some(SystemTime::now());
+        self.job = None;
+        self.pending_results = None;
+        self.parse_error = None;
+
+        if !repeat {
+            self.results.clear();
+            self.columns.clear();
+            self.columns_sampled = 0;
+            self.filter = None;
+            if self.expression.trim().is_empty() {
+                return;
+            }
+            match JqFilter::parse(&self.expression) {
+                Ok(filter) => self.filter = Some(Arc::new(filter)),
+                Err(e) => {
+                    self.parse_error = Some(e.to_string());
+                    return;
+                }
+            }
+        }
+        let Some(ref filter) = self.filter else {
+            return;
+        };
+
+        if repeat {
+            self.pending_results = Some(vec![]);
+        }
+
+        let filter = filter.clone();
+        let evaluation_errors = Arc::new(EvaluationErrors::default());
+        self.evaluation_errors = evaluation_errors.clone();
+        let new_rows: Arc<Mutex<Vec<JqRow>>> = Default::default();
+        self.new_rows = new_rows.clone();
+        self.job = Some(SearchJob::start_line_filter(
+            log_reader.line_index(),
+            0,
+            move |line_num, line| {
+                let Some(log_entry) = LogFileReader::parse_logline(line) else {
+                    return false;
+                };
+                match filter.evaluate(&log_entry.object) {
+                    Ok(outputs) => {
+                        let has_outputs = !outputs.is_empty();
+                        let rows = outputs
+                            .into_iter()
+                            .enumerate()
+                            .map(|(output_index, value)| JqRow {
+                                line_num,
+                                output_index,
+                                value,
+                            });
+                        new_rows.lock().unwrap().extend(rows);
+                        has_outputs
+                    }
+                    Err(e) => {
+                        if evaluation_errors.count.fetch_add(1, Ordering::Relaxed) == 0 {
+                            *evaluation_errors.first.lock().unwrap() = Some((line_num, e));
+                        }
+                        false
+                    }
+                }
+            },
+        ));
+    }
+
+    /// Collects results streamed from the running evaluation.
+    fn poll_job(&mut self, ui: &Ui) {
+        let Some(job) = &mut self.job else {
+            return;
+        };
+
+        // The rows are taken from the worker directly; the matched lines only drain the job.
+        job.poll(&mut vec![]);
+        let mut new_rows = std::mem::take(&mut *self.new_rows.lock().unwrap());
+        match self.pending_results {
+            Some(ref mut pending_results) => pending_results.append(&mut new_rows),
+            None => self.results.append(&mut new_rows),
+        }
+
+        if !job.is_finished() {
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+            return;
+        }
+
+        if let Some(error) = job.error() {
+            error!("Failed to evaluate jq expression: {:?}", error);
+        } else if let Some(pending_results) = self.pending_results.take() {
+            self.results = pending_results;
+            self.columns_sampled = 0;
+        }
+        self.pending_results = None;
+        self.job = None;
+    }
+
+    /// Chooses the columns from the keys of the first outputs, in order of appearance.
+    /// Outputs that aren't objects get a single value column.
+    fn update_columns(&mut self) {
+        let sample_len = self.results.len().min(COLUMN_SAMPLE_LEN);
+        if sample_len == self.columns_sampled {
+            return;
+        }
+
+        let mut columns: Vec<String> = vec![];
+        for row in &self.results[..sample_len] {
+            let JsonValue::Object(ref object) = row.value else {
+                if !columns.iter().any(|c| c == VALUE_COLUMN) {
+                    columns.insert(0, VALUE_COLUMN.to_string());
+                }
+                continue;
+            };
+            for (key, _) in object.iter() {
+                if !columns.iter().any(|c| c == key) {
+                    columns.push(key.to_string());
+                }
+            }
+        }
+        self.columns = columns;
+        self.columns_sampled = sample_len;
+    }
+
+    fn ui_toolbar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.expression)
+                    .hint_text("select(.level == \"ERROR\") | {t, tag, msg: .message}")
+                    .code_editor()
+                    .desired_width(450.0),
+            );
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.expression_changed = true;
+            }
+            if ui.button("Run").clicked() {
+                self.expression_changed = true;
+            }
+
+            if let Some(ref job) = self.job {
+                let (bytes_scanned, total_bytes) = job.progress();
+                ui.spinner();
+                ui.label(format!(
+                    "Evaluating... {} of {}",
+                    format_bytes(bytes_scanned),
+                    format_bytes(total_bytes)
+                ));
+                if ui.button("Cancel").clicked() {
+                    self.job = None;
+                    self.pending_results = None;
+                }
+            } else if self.filter.is_some() {
+                ui.label(format!("{} outputs", self.results.len()));
+            }
+
+            if let Some(ref error) = self.parse_error {
+                ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+            }
+
+            let error_count = self.evaluation_errors.count.load(Ordering::Relaxed);
+            if error_count > 0 {
+                let label = ui.label(
+                    RichText::new(format!("{} errors", error_count))
+                        .color(ui.visuals().warn_fg_color),
+                );
+                if let Some((line_num, ref message)) = *self.evaluation_errors.first.lock().unwrap()
+                {
+                    label.on_hover_text(format!("Line {}: {}", line_num + 1, message));
+                }
+            }
+        });
+        ui.separator();
+    }
+}
+
+/// Formats a projected value for a table cell. Strings are shown without quotes.
+fn cell_text(value: &JsonValue) -> String {
+    match value.as_str() {
+        Some(text) => text.to_string(),
+        None => value.dump(),
+    }
+}
+
+impl LogViewTabTrait for JqTab {
+    fn title(&self) -> egui::WidgetText {
+        "🔧 jq".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        self.poll_job(ui);
+        self.ui_toolbar(ui);
+
+        if self.expression_changed {
+            self.expression_changed = false;
+            self.execute(log_reader, false);
+        } else if let (None, Some(last_run_time), Some(load_time)) =
+            (&self.job, self.last_run_time, log_reader.load_time_point())
+        {
+            // A running evaluation is left to finish, then re-run if the file changed meanwhile.
+            if last_run_time < load_time {
+                self.execute(log_reader, true);
+            }
+        }
+
+        self.update_columns();
+        let columns = self.columns.clone();
+        let mut clicked_line = None;
+
+        let mut table_builder = TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(60.0));
+        for index in 0..columns.len() {
+            table_builder = if index + 1 == columns.len() {
+                table_builder.column(Column::remainder().clip(true))
+            } else {
+                table_builder.column(Column::initial(150.0).resizable(true).clip(true))
+            };
+        }
+
+        if self.scrolled_to_line != viewer_state.selected_line_num {
+            self.scrolled_to_line = viewer_state.selected_line_num;
+            if let Some(selected_line) = viewer_state.selected_line_num {
+                let row = self
+                    .results
+                    .partition_point(|result| result.line_num < selected_line);
+                if self
+                    .results
+                    .get(row)
+                    .is_some_and(|result| result.line_num == selected_line)
+                {
+                    table_builder = table_builder.scroll_to_row(row, Some(Align::Center));
+                }
+            }
+        }
+
+        table_builder
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.label(RichText::new("Line").strong());
+                });
+                for column in &columns {
+                    header.col(|ui| {
+                        ui.label(RichText::new(column).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, self.results.len(), |mut row| {
+                    let result = &self.results[row.index()];
+                    let line_num = result.line_num;
+                    row.set_selected(viewer_state.selected_line_num == Some(line_num));
+
+                    row.col(|ui| {
+                        ui.label(RichText::new((line_num + 1).to_string()).monospace());
+                        if result.output_index > 0 {
+                            ui.weak(format!("#{}", result.output_index + 1))
+                                .on_hover_text("Further output of the same line");
+                        }
+                    });
+                    for column in &columns {
+                        row.col(|ui| {
+                            let value = match &result.value {
+                                JsonValue::Object(object) => object.get(column),
+                                value if column == VALUE_COLUMN => Some(value),
+                                _ => None,
+                            };
+                            if let Some(value) = value {
+                                ui.label(RichText::new(cell_text(value)).monospace());
+                            }
+                        });
+                    }
+
+                    if row.response().clicked() {
+                        clicked_line = Some(line_num);
+                    }
+                });
+            });
+
+        if clicked_line.is_some() {
+            viewer_state.selected_line_num = clicked_line;
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use std::time::Duration;
+
+use egui::{Event, Key, KeyboardShortcut, Modifiers, RichText};
+
+/// An action that can be bound to keys and run from the command palette.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum Command {
+    ShowPalette,
+    OpenFile,
+    OpenRecent,
+    NewSearchTab,
+    SearchAllFiles,
+    ComparePatterns,
+    FocusSearch,
+    GoTo,
+    ToggleTail,
+    AddColumn,
+    RemoveColumn,
+    ResetUi,
+    KeyboardShortcuts,
+    PreviousLine,
+    NextLine,
+    PreviousPage,
+    NextPage,
+    FirstLine,
+    LastLine,
+    NextSearchHit,
+    PreviousSearchHit,
+    FocusContext,
+    FocusLog,
+    ToggleBookmark,
+    NextBookmark,
+    PreviousBookmark,
+    EditNote,
+    ExportTimeline,
+    /// A command saved by a newer version of the app, ignored when loading.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Command {
+    pub const ALL: [Command; 28] = [
+        Command::ShowPalette,
+        Command::OpenFile,
+        Command::OpenRecent,
+        Command::NewSearchTab,
+        Command::SearchAllFiles,
+        Command::ComparePatterns,
+        Command::FocusSearch,
+        Command::GoTo,
+        Command::ToggleTail,
+        Command::AddColumn,
+        Command::RemoveColumn,
+        Command::ResetUi,
+        Command::KeyboardShortcuts,
+        Command::PreviousLine,
+        Command::NextLine,
+        Command::PreviousPage,
+        Command::NextPage,
+        Command::FirstLine,
+        Command::LastLine,
+        Command::NextSearchHit,
+        Command::PreviousSearchHit,
+        Command::FocusContext,
+        Command::FocusLog,
+        Command::ToggleBookmark,
+        Command::NextBookmark,
+        Command::PreviousBookmark,
+        Command::EditNote,
+        Command::ExportTimeline,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::ShowPalette => "Command Palette",
+            Command::OpenFile => "Open File…",
+            Command::OpenRecent => "Open Recent File…",
+            Command::NewSearchTab => "New Search Tab",
+            Command::SearchAllFiles => "Search All Open Files…",
+            Command::ComparePatterns => "Compare Patterns of Two Files…",
+            Command::FocusSearch => "Focus Search Box",
+            Command::GoTo => "Go to Line or Time…",
+            Command::ToggleTail => "Toggle Tail",
+            Command::AddColumn => "Add Column…",
+            Command::RemoveColumn => "Remove Column…",
+            Command::ResetUi => "Reset UI",
+            Command::KeyboardShortcuts => "Keyboard Shortcuts…",
+            Command::PreviousLine => "Select Previous Line",
+            Command::NextLine => "Select Next Line",
+            Command::PreviousPage => "Page Up",
+            Command::NextPage => "Page Down",
+            Command::FirstLine => "Select First Line",
+            Command::LastLine => "Select Last Line",
+            Command::NextSearchHit => "Next Search Result",
+            Command::PreviousSearchHit => "Previous Search Result",
+            Command::FocusContext => "Focus Context Tab",
+            Command::FocusLog => "Focus Log Tab",
+            Command::ToggleBookmark => "Toggle Bookmark",
+            Command::NextBookmark => "Next Bookmark",
+            Command::PreviousBookmark => "Previous Bookmark",
+            Command::EditNote => "Edit Note on Selected Line…",
+            Command::ExportTimeline => "Export Incident Timeline…",
+            Command::Unknown => "Unknown Command",
+        }
+    }
+}
+
+/// A key sequence bound to a command. Most bindings are a single shortcut.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct KeyBinding {
+    pub command: Command,
+    pub keys: Vec<KeyboardShortcut>,
+}
+
+impl KeyBinding {
+    fn new(command: Command, modifiers: Modifiers, key: Key) -> Self {
+        Self {
+            command,
+            keys: vec![KeyboardShortcut::new(modifiers, key)],
+        }
+    }
+
+    pub fn format(&self, ctx: &egui::Context) -> String {
+        self.keys
+            .iter()
+            .map(|shortcut| ctx.format_shortcut(shortcut))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Key sequences longer than one key must be completed within this time.
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The user's key bindings, persisted with the app.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(from = "SavedKeyBindings", into = "SavedKeyBindings")]
+pub struct KeyBindings {
+    bindings: Vec<KeyBinding>,
+    /// Keys of a sequence typed so far, and when the last one was pressed.
+    pending_keys: Vec<KeyboardShortcut>,
+    pending_time: f64,
+}
+
+/// Key bindings as persisted: only the commands whose keys differ from the defaults,
+/// so that commands added later still get their default keys.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct SavedKeyBindings {
+    overrides: Vec<CommandKeys>,
+}
+
+/// All key sequences bound to a command, none if the user removed them.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct CommandKeys {
+    command: Command,
+    keys: Vec<Vec<KeyboardShortcut>>,
+}
+
+impl From<SavedKeyBindings> for KeyBindings {
+    fn from(saved: SavedKeyBindings) -> Self {
+        let mut key_bindings = Self::default();
+        for CommandKeys { command, keys } in saved.overrides {
+            if command == Command::Unknown {
+                continue;
+            }
+            key_bindings.bindings.retain(|b| b.command != command);
+            key_bindings
+                .bindings
+                .extend(keys.into_iter().map(|keys| KeyBinding { command, keys }));
+        }
+        key_bindings
+    }
+}
+
+impl From<KeyBindings> for SavedKeyBindings {
+    fn from(key_bindings: KeyBindings) -> Self {
+        let defaults = KeyBindings::default();
+        let keys_of = |key_bindings: &KeyBindings, command| -> Vec<Vec<KeyboardShortcut>> {
+            key_bindings
+                .bindings_for(command)
+                .map(|b| b.keys.clone())
+                .collect()
+        };
+        let overrides = Command::ALL
+            .into_iter()
+            .filter_map(|command| {
+                let keys = keys_of(&key_bindings, command);
+                (keys != keys_of(&defaults, command)).then_some(CommandKeys { command, keys })
+            })
+            .collect();
+        Self { overrides }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Self::default_preset(),
+            pending_keys: vec![],
+            pending_time: 0.0,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn default_preset() -> Vec<KeyBinding> {
+        use Command::*;
+        vec![
+            KeyBinding::new(ShowPalette, Modifiers::COMMAND | Modifiers::SHIFT, Key::P),
+            KeyBinding::new(OpenFile, Modifiers::COMMAND, Key::O),
+            KeyBinding::new(NewSearchTab, Modifiers::COMMAND | Modifiers::SHIFT, Key::F),
+            KeyBinding::new(FocusSearch, Modifiers::COMMAND, Key::F),
+            KeyBinding::new(GoTo, Modifiers::COMMAND, Key::G),
+            KeyBinding::new(ToggleTail, Modifiers::COMMAND, Key::T),
+            KeyBinding::new(PreviousLine, Modifiers::NONE, Key::ArrowUp),
+            KeyBinding::new(NextLine, Modifiers::NONE, Key::ArrowDown),
+            KeyBinding::new(PreviousPage, Modifiers::NONE, Key::PageUp),
+            KeyBinding::new(NextPage, Modifiers::NONE, Key::PageDown),
+            KeyBinding::new(FirstLine, Modifiers::NONE, Key::Home),
+            KeyBinding::new(LastLine, Modifiers::NONE, Key::End),
+            KeyBinding::new(NextSearchHit, Modifiers::NONE, Key::N),
+            KeyBinding::new(PreviousSearchHit, Modifiers::SHIFT, Key::N),
+            KeyBinding::new(FocusContext, Modifiers::NONE, Key::Enter),
+            KeyBinding::new(FocusLog, Modifiers::NONE, Key::Escape),
+            KeyBinding::new(ToggleBookmark, Modifiers::COMMAND, Key::B),
+            KeyBinding::new(NextBookmark, Modifiers::NONE, Key::F2),
+            KeyBinding::new(PreviousBookmark, Modifiers::SHIFT, Key::F2),
+            KeyBinding::new(EditNote, Modifiers::COMMAND, Key::E),
+        ]
+    }
+
+    /// The default bindings plus j/k, gg/G, / and m for vim users.
+    pub fn vim_preset() -> Vec<KeyBinding> {
+        use Command::*;
+        let mut bindings = Self::default_preset();
+        bindings.extend([
+            KeyBinding::new(PreviousLine, Modifiers::NONE, Key::K),
+            KeyBinding::new(NextLine, Modifiers::NONE, Key::J),
+            KeyBinding {
+                command: FirstLine,
+                keys: vec![
+                    KeyboardShortcut::new(Modifiers::NONE, Key::G),
+                    KeyboardShortcut::new(Modifiers::NONE, Key::G),
+                ],
+            },
+            KeyBinding::new(LastLine, Modifiers::SHIFT, Key::G),
+            KeyBinding::new(FocusSearch, Modifiers::NONE, Key::Slash),
+            KeyBinding::new(ToggleBookmark, Modifiers::NONE, Key::M),
+        ]);
+        bindings
+    }
+
+    /// Bindings of the given command.
+    pub fn bindings_for(&self, command: Command) -> impl Iterator<Item = &KeyBinding> {
+        self.bindings.iter().filter(move |b| b.command == command)
+    }
+
+    /// The first binding of the command formatted for display, e.g. "Ctrl+G".
+    pub fn shortcut_text(&self, ctx: &egui::Context, command: Command) -> Option<String> {
+        self.bindings_for(command).next().map(|b| b.format(ctx))
+    }
+
+    /// Consumes the key presses bound to commands and returns the commands.
+    /// While a widget has keyboard focus, a popup is open or `dialog_open` is set, only
+    /// shortcuts using Ctrl, Cmd or Alt are handled, so that Enter, Escape and the arrows
+    /// still reach the widget or dialog.
+    pub fn read_commands(&mut self, ctx: &egui::Context, dialog_open: bool) -> Vec<Command> {
+        let keys_taken = dialog_open || ctx.memory(|m| m.focused().is_some() || m.any_popup_open());
+        let mut commands = vec![];
+
+        ctx.input_mut(|i| {
+            if i.time - self.pending_time > SEQUENCE_TIMEOUT.as_secs_f64() {
+                self.pending_keys.clear();
+            }
+
+            i.events.retain(|event| {
+                let Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } = event
+                else {
+                    return true;
+                };
+                if keys_taken && !(modifiers.command || modifiers.ctrl || modifiers.alt) {
+                    return true;
+                }
+
+                self.pending_keys
+                    .push(KeyboardShortcut::new(*modifiers, *key));
+                match self.match_pending_keys() {
+                    SequenceMatch::Complete(command) => {
+                        commands.push(command);
+                        self.pending_keys.clear();
+                        false
+                    }
+                    SequenceMatch::Partial => {
+                        self.pending_time = i.time;
+                        false
+                    }
+                    SequenceMatch::None if self.pending_keys.len() > 1 => {
+                        // Start over from this key in case it begins a binding by itself.
+                        self.pending_keys.drain(..self.pending_keys.len() - 1);
+                        match self.match_pending_keys() {
+                            SequenceMatch::Complete(command) => {
+                                commands.push(command);
+                                self.pending_keys.clear();
+                                false
+                            }
+                            SequenceMatch::Partial => {
+                                self.pending_time = i.time;
+                                false
+                            }
+                            SequenceMatch::None => {
+                                self.pending_keys.clear();
+                                true
+                            }
+                        }
+                    }
+                    SequenceMatch::None => {
+                        self.pending_keys.clear();
+                        true
+                    }
+                }
+            });
+        });
+
+        commands
+    }
+
+    fn match_pending_keys(&self) -> SequenceMatch {
+        let pending = &self.pending_keys;
+        let mut partial = false;
+        for binding in &self.bindings {
+            if binding.keys.len() < pending.len() {
+                continue;
+            }
+            let prefix_matches = binding.keys.iter().zip(pending).all(|(bound, pressed)| {
+                bound.logical_key == pressed.logical_key
+                    && pressed.modifiers.matches_exact(bound.modifiers)
+            });
+            if !prefix_matches {
+                continue;
+            }
+            if binding.keys.len() == pending.len() {
+                return SequenceMatch::Complete(binding.command);
+            }
+            partial = true;
+        }
+        if partial {
+            SequenceMatch::Partial
+        } else {
+            SequenceMatch::None
+        }
+    }
+}
+
+enum SequenceMatch {
+    Complete(Command),
+    Partial,
+    None,
+}
+
+/// Window for remapping key bindings.
+pub struct KeyBindingsWindow {
+    /// Command whose next key press is being recorded as a new binding.
+    recording: Option<Command>,
+}
+
+impl KeyBindingsWindow {
+    pub fn new() -> Self {
+        Self { recording: None }
+    }
+
+    /// True while waiting for the key of a new binding.
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Shows the window. Returns false once it has been closed.
+    pub fn show(&mut self, ctx: &egui::Context, key_bindings: &mut KeyBindings) -> bool {
+        if let Some(command) = self.recording {
+            let recorded_key = ctx.input_mut(|i| {
+                let mut recorded_key = None;
+                i.events.retain(|event| match event {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } if recorded_key.is_none() => {
+                        recorded_key = Some(KeyboardShortcut::new(*modifiers, *key));
+                        false
+                    }
+                    _ => true,
+                });
+                recorded_key
+            });
+            if let Some(shortcut) = recorded_key {
+                key_bindings.bindings.push(KeyBinding {
+                    command,
+                    keys: vec![shortcut],
+                });
+                self.recording = None;
+            }
+        }
+
+        let mut open = true;
+        egui::Window::new("Keyboard Shortcuts")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Preset:");
+                    if ui.button("Default").clicked() {
+                        key_bindings.bindings = KeyBindings::default_preset();
+                    }
+                    if ui.button("Vim").on_hover_text("j/k, gg/G and /").clicked() {
+                        key_bindings.bindings = KeyBindings::vim_preset();
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("key_bindings")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for command in Command::ALL {
+                                ui.label(command.name());
+
+                                ui.horizontal(|ui| {
+                                    let mut binding_to_remove = None;
+                                    for (index, binding) in key_bindings.bindings.iter().enumerate()
+                                    {
+                                        if binding.command == command
+                                            && ui
+                                                .button(
+                                                    RichText::new(binding.format(ctx)).monospace(),
+                                                )
+                                                .on_hover_text("Click to remove")
+                                                .clicked()
+                                        {
+                                            binding_to_remove = Some(index);
+                                        }
+                                    }
+                                    if let Some(index) = binding_to_remove {
+                                        key_bindings.bindings.remove(index);
+                                    }
+
+                                    if self.recording == Some(command) {
+                                        ui.label("Press a key…");
+                                    } else if ui
+                                        .button("➕")
+                                        .on_hover_text("Add Shortcut")
+                                        .clicked()
+                                    {
+                                        self.recording = Some(command);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        if !open {
+            self.recording = None;
+        }
+        open
+    }
+}
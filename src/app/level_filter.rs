@@ -0,0 +1,200 @@
+use super::{
+    log_analysis::{BackgroundAnalysis, LineAnalysis},
+    log_entries_table::{next_lines_generation, TableLines},
+    log_file_reader::{LineNumber, LogFileReader},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+    /// Unknown or missing level, or a line that isn't a log entry.
+    Other,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 6] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+        LogLevel::Fatal,
+        LogLevel::Other,
+    ];
+
+    pub fn from_name(level: &str) -> Self {
+        match level.to_ascii_uppercase().as_str() {
+            "DEBUG" => LogLevel::Debug,
+            "INFO" => LogLevel::Info,
+            "WARNING" | "WARN" => LogLevel::Warning,
+            "ERROR" => LogLevel::Error,
+            "FATAL" | "CRITICAL" => LogLevel::Fatal,
+            _ => LogLevel::Other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+            LogLevel::Other => "other",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The level of every line of a log file.
+#[derive(Default)]
+pub struct LevelIndex {
+    levels: Vec<LogLevel>,
+}
+
+impl LevelIndex {
+    /// Level of the given line, if it has been analyzed.
+    pub fn level(&self, line_num: LineNumber) -> Option<LogLevel> {
+        self.levels.get(line_num).copied()
+    }
+}
+
+impl LineAnalysis for LevelIndex {
+    type Line = LogLevel;
+
+    fn parse_line(line: &str) -> LogLevel {
+        match LogFileReader::parse_logline(line) {
+            Some(log_entry) => log_entry.object["level"]
+                .as_str()
+                .map_or(LogLevel::Other, LogLevel::from_name),
+            None => LogLevel::Other,
+        }
+    }
+
+    fn add_line(&mut self, _line_num: LineNumber, level: LogLevel) {
+        self.levels.push(level);
+    }
+}
+
+/// Hides lines of a log table by level, and counts the lines of each level.
+/// Lines are classified incrementally as the level analysis progresses, so the
+/// work is done once per load rather than every frame.
+pub struct LevelFilter {
+    shown: [bool; LogLevel::ALL.len()],
+    counts: [usize; LogLevel::ALL.len()],
+    /// Lines of the source with a shown level. Only kept while some level is hidden.
+    visible_lines: Vec<LineNumber>,
+    /// Replaced whenever `visible_lines` is cleared.
+    visible_lines_generation: usize,
+    /// Number of source lines classified so far.
+    lines_consumed: usize,
+    source_fingerprint: Option<SourceFingerprint>,
+    generation: usize,
+}
+
+/// Identifies the lines a table was given, to notice when they're replaced.
+/// Lines with the same fingerprint only differ by lines added at the end.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SourceFingerprint {
+    Range {
+        start: LineNumber,
+    },
+    Lines {
+        generation: usize,
+        /// Tells apart slices of the list restricted to different time ranges.
+        first: Option<LineNumber>,
+    },
+}
+
+impl SourceFingerprint {
+    pub fn of(source: &TableLines<'_>) -> Self {
+        match source {
+            TableLines::Range(range) => SourceFingerprint::Range { start: range.start },
+            TableLines::Lines(lines, generation) => SourceFingerprint::Lines {
+                generation: *generation,
+                first: lines.first().copied(),
+            },
+        }
+    }
+}
+
+impl LevelFilter {
+    pub fn new() -> Self {
+        Self {
+            shown: [true; LogLevel::ALL.len()],
+            counts: Default::default(),
+            visible_lines: vec![],
+            visible_lines_generation: next_lines_generation(),
+            lines_consumed: 0,
+            source_fingerprint: None,
+            generation: 0,
+        }
+    }
+
+    /// True if any level is hidden.
+    pub fn is_active(&self) -> bool {
+        self.shown.iter().any(|shown| !shown)
+    }
+
+    pub fn is_shown(&self, level: LogLevel) -> bool {
+        self.shown[level.index()]
+    }
+
+    pub fn toggle(&mut self, level: LogLevel) {
+        self.shown[level.index()] = !self.shown[level.index()];
+        self.reset();
+    }
+
+    /// Number of source lines of the given level classified so far.
+    pub fn count(&self, level: LogLevel) -> usize {
+        self.counts[level.index()]
+    }
+
+    /// Lines of the source with a shown level, or None if no level is hidden.
+    pub fn visible_lines(&self) -> Option<TableLines<'_>> {
+        self.is_active()
+            .then(|| TableLines::Lines(&self.visible_lines, self.visible_lines_generation))
+    }
+
+    /// Classifies the lines of `source` not seen yet.
+    pub fn update(&mut self, source: &TableLines<'_>, log_levels: &BackgroundAnalysis<LevelIndex>) {
+        let level_index = log_levels.lock();
+
+        let source_fingerprint = SourceFingerprint::of(source);
+        if log_levels.generation() != self.generation
+            || Some(&source_fingerprint) != self.source_fingerprint.as_ref()
+            || source.len() < self.lines_consumed
+        {
+            self.reset();
+            self.generation = log_levels.generation();
+            self.source_fingerprint = Some(source_fingerprint);
+        }
+
+        let is_active = self.is_active();
+        while self.lines_consumed < source.len() {
+            let line_num = source.get(self.lines_consumed);
+            let Some(level) = level_index.level(line_num) else {
+                break;
+            };
+
+            self.counts[level.index()] += 1;
+            if is_active && self.is_shown(level) {
+                self.visible_lines.push(line_num);
+            }
+            self.lines_consumed += 1;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.counts = Default::default();
+        self.visible_lines.clear();
+        self.visible_lines_generation = next_lines_generation();
+        self.lines_consumed = 0;
+    }
+}
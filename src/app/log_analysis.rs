@@ -0,0 +1,177 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::SystemTime,
+};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use log::error;
+
+use super::log_file_reader::{LineIndex, LineNumber, LogFileReader};
+
+/// Number of lines analyzed between checks for a newer file load.
+const BATCH_SIZE: usize = 4096;
+
+/// A summary computed by visiting every line of a log file in order.
+/// Lines are parsed with `parse_line` before the analysis is locked, so that only
+/// `add_line` holds up readers of the analysis.
+pub trait LineAnalysis: Default + Send + 'static {
+    /// What the analysis needs to know about a line.
+    type Line: Send;
+
+    fn parse_line(line: &str) -> Self::Line;
+
+    fn add_line(&mut self, line_num: LineNumber, line: Self::Line);
+}
+
+#[derive(Default)]
+struct Progress {
+    lines_analyzed: AtomicUsize,
+    line_count: AtomicUsize,
+    resets: AtomicUsize,
+}
+
+/// Runs a `LineAnalysis` over a log file on a worker thread.
+/// When the file is reloaded and has only grown, new lines are added to the existing
+/// analysis. Otherwise the analysis is started over.
+pub struct BackgroundAnalysis<A: LineAnalysis> {
+    analysis: Arc<Mutex<A>>,
+    progress: Arc<Progress>,
+    index_sender: Option<Sender<LineIndex>>,
+    synced_load_time: Option<SystemTime>,
+}
+
+impl<A: LineAnalysis> Default for BackgroundAnalysis<A> {
+    fn default() -> Self {
+        Self {
+            analysis: Default::default(),
+            progress: Default::default(),
+            index_sender: None,
+            synced_load_time: None,
+        }
+    }
+}
+
+impl<A: LineAnalysis> BackgroundAnalysis<A> {
+    /// Starts the worker on first use, and hands it the latest line index whenever
+    /// the file has been reloaded. Cheap enough to call every frame.
+    pub fn sync(&mut self, log_reader: &LogFileReader) {
+        if log_reader.load_time_point().is_none()
+            || log_reader.load_time_point() == self.synced_load_time
+        {
+            return;
+        }
+        self.synced_load_time = log_reader.load_time_point();
+
+        let line_index = log_reader.line_index();
+        self.progress
+            .line_count
+            .store(line_index.line_count(), Ordering::Relaxed);
+
+        let sender = self.index_sender.get_or_insert_with(|| {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            let analysis = self.analysis.clone();
+            let progress = self.progress.clone();
+            thread::spawn(move || Self::run_worker(analysis, progress, receiver));
+            sender
+        });
+
+        if sender.send(line_index).is_err() {
+            error!("Background analysis worker exited unexpectedly.");
+        }
+    }
+
+    /// Locks the analysis for reading. The worker is blocked while the lock is held.
+    pub fn lock(&self) -> MutexGuard<'_, A> {
+        self.analysis.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the number of lines analyzed so far and the total number of lines.
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.progress.lines_analyzed.load(Ordering::Relaxed),
+            self.progress.line_count.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Changes whenever the analysis is started over, so results derived from an
+    /// earlier analysis can be discarded. Read it while holding the lock.
+    pub fn generation(&self) -> usize {
+        self.progress.resets.load(Ordering::Relaxed)
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.index_sender.is_some()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let (lines_analyzed, line_count) = self.progress();
+        self.is_started() && lines_analyzed >= line_count
+    }
+
+    fn run_worker(analysis: Arc<Mutex<A>>, progress: Arc<Progress>, receiver: Receiver<LineIndex>) {
+        let mut analyzed_index: Option<LineIndex> = None;
+        let mut lines_analyzed = 0;
+
+        while let Ok(mut line_index) = receiver.recv() {
+            loop {
+                // Always work on the most recent load.
+                match receiver.try_recv() {
+                    Ok(newer_index) => {
+                        line_index = newer_index;
+                        continue;
+                    }
+                    Err(TryRecvError::Disconnected) => return,
+                    Err(TryRecvError::Empty) => {}
+                }
+
+                if !Self::is_continuation(analyzed_index.as_ref(), lines_analyzed, &line_index) {
+                    let mut analysis_guard = analysis.lock().unwrap_or_else(|e| e.into_inner());
+                    *analysis_guard = A::default();
+                    progress.resets.fetch_add(1, Ordering::Relaxed);
+                    lines_analyzed = 0;
+                }
+                analyzed_index = Some(line_index.clone());
+                progress
+                    .lines_analyzed
+                    .store(lines_analyzed, Ordering::Relaxed);
+
+                if lines_analyzed >= line_index.line_count() {
+                    break;
+                }
+
+                // Read and parse the batch before locking, so the UI isn't kept waiting.
+                let batch = lines_analyzed..(lines_analyzed + BATCH_SIZE);
+                let mut parsed_lines = Vec::with_capacity(BATCH_SIZE);
+                let result = line_index.for_each_line(batch, |line_num, line| {
+                    parsed_lines.push((line_num, A::parse_line(line)));
+                    true
+                });
+                if let Err(e) = result {
+                    error!("Background analysis failed to read lines: {:?}", e);
+                    break;
+                }
+
+                let mut analysis_guard = analysis.lock().unwrap_or_else(|e| e.into_inner());
+                for (line_num, line) in parsed_lines {
+                    analysis_guard.add_line(line_num, line);
+                    lines_analyzed = line_num + 1;
+                }
+            }
+        }
+    }
+
+    /// True if `line_index` describes the same file as `analyzed_index` with only
+    /// lines appended after the ones already analyzed.
+    fn is_continuation(
+        analyzed_index: Option<&LineIndex>,
+        lines_analyzed: LineNumber,
+        line_index: &LineIndex,
+    ) -> bool {
+        analyzed_index
+            .is_some_and(|analyzed_index| line_index.extends(analyzed_index, lines_analyzed))
+    }
+}
@@ -1,7 +1,7 @@
 use super::{
     log_entries_table::LogEntriesTable,
     log_file_reader::LogFileReader,
-    log_view::{LogViewerState, LogViewTabTrait},
+    log_view::{LogViewerState, LogViewTabTrait, TabKind},
 };
 
 pub struct LogEntriesTab {
@@ -21,13 +21,18 @@ impl LogViewTabTrait for LogEntriesTab {
         "📃 Log".into()
     }
 
+    fn kind(&self) -> TabKind {
+        TabKind::Log
+    }
+
     fn ui(
         &mut self,
         ui: &mut egui::Ui,
         log_reader: &mut LogFileReader,
         viewer_state: &mut LogViewerState,
     ) {
+        let highlight = viewer_state.search_highlight.clone();
         self.log_entries_table
-            .ui(ui, log_reader, viewer_state, None, |_| {});
+            .ui(ui, log_reader, viewer_state, None, None, highlight.as_ref(), |_| {});
     }
 }
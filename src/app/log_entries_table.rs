@@ -1,59 +1,338 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use egui::{Align, Button, Color32, CursorIcon, Response, RichText, Ui};
+use egui::{
+    Align, Align2, Button, Color32, CursorIcon, FontId, Response, RichText, Sense, TextFormat, Ui,
+    WidgetText,
+};
 use egui::Frame;
+use egui::text::LayoutJob;
 use egui_extras::{Column, TableBuilder, TableRow};
 use egui_toast::ToastKind;
+use regex::Regex;
+
+use crate::app::log_view::{ColumnTextColor, LogViewerState, Navigation};
+
+use super::{
+    level_filter::{LevelFilter, LogLevel, SourceFingerprint},
+    log_file_reader::{LineNumber, LogFileReader},
+    repeats::{CollapseMode, RepeatCollapser, Run},
+    time_histogram::{row_at_time, HistogramAction, TimeHistogram},
+    time_range::RangeLines,
+    time_scroll_bar::TimeScrollBar,
+    timestamp::Timestamp,
+};
+
+/// Starts a new generation for a list of lines shown by a table, for when the list is
+/// replaced rather than appended to. Generations are unique across all lists, so the
+/// table's caches notice when they're given a different one.
+pub fn next_lines_generation() -> usize {
+    static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(1);
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Lines shown by a log table, in ascending order.
+#[derive(Clone)]
+pub enum TableLines<'a> {
+    Range(Range<LineNumber>),
+    /// Listed lines, with the generation of the list.
+    Lines(&'a [LineNumber], usize),
+}
+
+impl<'a> TableLines<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            TableLines::Range(range) => range.len(),
+            TableLines::Lines(lines, _) => lines.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-use crate::app::log_view::{ColumnTextColor, LogViewerState};
+    /// Line number shown in the given row.
+    pub fn get(&self, row: usize) -> LineNumber {
+        match self {
+            TableLines::Range(range) => range.start + row,
+            TableLines::Lines(lines, _) => lines[row],
+        }
+    }
+
+    /// Maps a line number to a table row, if the line is shown.
+    pub fn row_of(&self, line_num: LineNumber) -> Option<usize> {
+        match self {
+            TableLines::Range(range) => range.contains(&line_num).then(|| line_num - range.start),
+            TableLines::Lines(lines, _) => lines.binary_search(&line_num).ok(),
+        }
+    }
+
+    /// Row of the given line, or of the first shown line after it if it isn't shown.
+    pub fn nearest_row(&self, line_num: LineNumber) -> usize {
+        let row = match self {
+            TableLines::Range(range) => line_num.saturating_sub(range.start),
+            TableLines::Lines(lines, _) => lines.partition_point(|&l| l < line_num),
+        };
+        row.min(self.len().saturating_sub(1))
+    }
+
+    /// Only the lines within `range`.
+    pub fn restrict(self, range: &Range<LineNumber>) -> TableLines<'a> {
+        match self {
+            TableLines::Range(lines) => {
+                let start = lines.start.max(range.start);
+                TableLines::Range(start..lines.end.min(range.end).max(start))
+            }
+            TableLines::Lines(lines, generation) => {
+                let start = lines.partition_point(|&line_num| line_num < range.start);
+                let end = lines.partition_point(|&line_num| line_num < range.end);
+                TableLines::Lines(&lines[start..end.max(start)], generation)
+            }
+        }
+    }
+}
+
+/// Lines present in both the table's filtered lines and the scanned time range,
+/// recomputed only when either changes.
+#[derive(Default)]
+struct TimeRangeIntersection {
+    /// Sources the lines were computed from, and their lengths.
+    key: Option<(SourceFingerprint, usize, usize, usize)>,
+    lines: Vec<LineNumber>,
+    /// Changes when either source is replaced. Sources that only grew extend the lines.
+    generation: usize,
+}
+
+impl TimeRangeIntersection {
+    fn intersect<'a>(
+        &'a mut self,
+        lines: &TableLines<'_>,
+        time_range_lines: &[LineNumber],
+        time_range_generation: usize,
+    ) -> TableLines<'a> {
+        let fingerprint = SourceFingerprint::of(lines);
+        let key = (
+            fingerprint,
+            lines.len(),
+            time_range_generation,
+            time_range_lines.len(),
+        );
+        if self.key.as_ref() != Some(&key) {
+            let sources_replaced = match &self.key {
+                Some((old_fingerprint, _, old_time_range_generation, _)) => {
+                    *old_fingerprint != key.0 || *old_time_range_generation != key.2
+                }
+                None => true,
+            };
+            if sources_replaced {
+                self.generation = next_lines_generation();
+            }
+            self.key = Some(key);
+            self.lines.clear();
+            let mut time_range_iter = time_range_lines.iter().peekable();
+            for row in 0..lines.len() {
+                let line_num = lines.get(row);
+                while time_range_iter.next_if(|&&t| t < line_num).is_some() {}
+                if time_range_iter.peek() == Some(&&line_num) {
+                    self.lines.push(line_num);
+                }
+            }
+        }
+        TableLines::Lines(&self.lines, self.generation)
+    }
+}
+
+/// How a row is drawn when showing search hits with context lines.
+#[derive(Clone, Copy, Default)]
+struct RowStyle {
+    /// The row is a context line rather than a hit.
+    dimmed: bool,
+    /// The row starts a group of lines not contiguous with the previous row.
+    separated: bool,
+}
+
+impl RowStyle {
+    fn text_color(&self, color: Color32) -> Color32 {
+        if self.dimmed {
+            color.gamma_multiply(0.5)
+        } else {
+            color
+        }
+    }
 
-use super::log_file_reader::{LineNumber, LogFileReader};
+    fn paint_separator(&self, ui: &Ui) {
+        if self.separated {
+            let rect = ui.max_rect();
+            let stroke = egui::Stroke::new(1.0, ui.visuals().weak_text_color());
+            ui.painter().hline(rect.x_range(), rect.top() - 1.0, stroke);
+        }
+    }
+}
 
 pub struct LogEntriesTable {
     selected_line: Option<usize>,
     scroll_to_selected: bool,
     sync_line_selection: bool,
     tail_log: bool,
+    level_filter: LevelFilter,
+    repeat_collapser: RepeatCollapser,
+    time_range_intersection: TimeRangeIntersection,
+    show_timeline: bool,
+    timeline: TimeHistogram,
+    /// Times of the first and last rows in view, as of the last frame.
+    viewport: Option<(Timestamp, Timestamp)>,
+    time_scroll_bar: TimeScrollBar,
 }
 
 impl LogEntriesTable {
-    fn add_tool_button(ui: &mut Ui, text: &str, hover_text: &str) -> Response {
-        ui.button(text)
-            .on_hover_cursor(CursorIcon::PointingHand)
-            .on_hover_text(hover_text)
-    }
-
     pub fn new() -> Self {
         Self {
             selected_line: None,
             scroll_to_selected: false,
             sync_line_selection: true,
             tail_log: false,
+            level_filter: LevelFilter::new(),
+            repeat_collapser: RepeatCollapser::new(),
+            time_range_intersection: Default::default(),
+            show_timeline: true,
+            timeline: Default::default(),
+            viewport: None,
+            time_scroll_bar: Default::default(),
         }
     }
 
+    /// Shows the table. If `context_of_hits` is given, `filtered_entries` are search hits
+    /// along with lines of context: lines other than the hits are dimmed, and
+    /// non-contiguous groups of lines are separated.
+    #[allow(clippy::too_many_arguments)]
     pub fn ui(
         &mut self,
         ui: &mut Ui,
         log_file_reader: &mut LogFileReader,
         viewer_state: &mut LogViewerState,
-        filtered_entries: Option<&[LineNumber]>,
+        filtered_entries: Option<TableLines<'_>>,
+        context_of_hits: Option<&[LineNumber]>,
+        highlight: Option<&Regex>,
         add_toolbar_contents: impl FnOnce(&mut Ui),
     ) {
         self.toolbar_ui(ui, log_file_reader, viewer_state, add_toolbar_contents);
 
-        let total_rows = match filtered_entries {
-            Some(lines) => lines.len(),
-            None => log_file_reader.line_count(),
+        let lines =
+            filtered_entries.unwrap_or_else(|| TableLines::Range(0..log_file_reader.line_count()));
+
+        let time_range_lines = viewer_state.time_range.lines();
+        let lines = match (&time_range_lines, lines) {
+            (None, lines) => lines,
+            (Some(RangeLines::Contiguous(range)), lines) => lines.restrict(range),
+            (Some(RangeLines::Scanned(scanned)), TableLines::Range(range)) => {
+                TableLines::Lines(scanned, viewer_state.time_range.generation()).restrict(&range)
+            }
+            (Some(RangeLines::Scanned(scanned)), lines) => self.time_range_intersection.intersect(
+                &lines,
+                scanned,
+                viewer_state.time_range.generation(),
+            ),
         };
 
+        self.level_filter.update(&lines, &viewer_state.log_levels);
+        let lines = self.level_filter.visible_lines().unwrap_or(lines);
+
+        if self.repeat_collapser.is_active() {
+            viewer_state.message_index.sync(log_file_reader);
+            if !viewer_state.message_index.is_complete() {
+                ui.ctx().request_repaint_after(Duration::from_millis(250));
+            }
+        }
+        self.repeat_collapser
+            .update(&lines, &viewer_state.message_index);
+        let lines = self.repeat_collapser.visible_lines().unwrap_or(lines);
+
+        let mut navigated_row = None;
+        if self.show_timeline {
+            viewer_state.time_volume.sync(log_file_reader);
+            if !viewer_state.time_volume.is_complete() {
+                ui.ctx().request_repaint_after(Duration::from_millis(250));
+            }
+            let action = self.timeline.ui(
+                ui,
+                &viewer_state.time_volume,
+                self.viewport,
+                viewer_state.time_range.bounds(),
+            );
+            match action {
+                Some(HistogramAction::JumpTo(time)) => {
+                    if let Some(row) = row_at_time(log_file_reader, &lines, time) {
+                        navigated_row = Some(row);
+                        self.selected_line = Some(lines.get(row));
+                        if self.sync_line_selection {
+                            viewer_state.selected_line_num = self.selected_line;
+                        }
+                        self.tail_log = false;
+                    }
+                }
+                Some(HistogramAction::SelectRange(start, end)) => {
+                    viewer_state.time_range.select_range(
+                        start,
+                        end,
+                        log_file_reader,
+                        &viewer_state.timestamp_order,
+                    );
+                }
+                None => {}
+            }
+        }
+
+        if let Some(navigation) = viewer_state.navigation.take() {
+            let page_rows = (ui.available_height() / (ROW_HEIGHT + ui.spacing().item_spacing.y))
+                as usize;
+            let current_row = self.selected_line.map(|line_num| lines.nearest_row(line_num));
+            navigated_row = match navigation {
+                Navigation::PreviousLine => current_row.map(|row| row.saturating_sub(1)),
+                Navigation::NextLine => current_row.map_or(Some(0), |row| Some(row + 1)),
+                Navigation::PreviousPage => current_row.map(|row| row.saturating_sub(page_rows)),
+                Navigation::NextPage => current_row.map_or(Some(0), |row| Some(row + page_rows)),
+                Navigation::FirstLine => Some(0),
+                Navigation::LastLine => Some(usize::MAX),
+                Navigation::ToggleTail => {
+                    self.tail_log = !self.tail_log;
+                    None
+                }
+                Navigation::NextSearchHit | Navigation::PreviousSearchHit => None,
+            }
+            .filter(|_| !lines.is_empty())
+            .map(|row| row.min(lines.len() - 1));
+
+            if let Some(row) = navigated_row {
+                self.selected_line = Some(lines.get(row));
+                if self.sync_line_selection {
+                    viewer_state.selected_line_num = self.selected_line;
+                }
+                self.tail_log &= navigation == Navigation::LastLine;
+            }
+        }
+
+        let mut body_rect = ui.available_rect_before_wrap();
+        body_rect.min.y += HEADER_HEIGHT + ui.spacing().item_spacing.y;
+        let is_scrollable =
+            lines.len() as f32 * (ROW_HEIGHT + ui.spacing().item_spacing.y) > body_rect.height();
+        if is_scrollable {
+            self.time_scroll_bar.update(log_file_reader, &lines);
+        }
+
         let mut table_builder = TableBuilder::new(ui)
             .max_scroll_height(f32::INFINITY)
             .cell_layout(egui::Layout::left_to_right(Align::Center))
             .striped(true)
             .auto_shrink(false)
             .min_scrolled_height(0.0)
-            .sense(egui::Sense::click());
+            .sense(egui::Sense::click())
+            .column(Column::exact(GUTTER_WIDTH))
+            .column(Column::exact(GUTTER_WIDTH));
+        if self.repeat_collapser.is_active() {
+            table_builder = table_builder.column(Column::auto().at_least(40.0));
+        }
 
         let mut col_iter = viewer_state.displayed_columns.iter().peekable();
         while let Some(col_key) = col_iter.next() {
@@ -72,16 +351,15 @@ impl LogEntriesTable {
             table_builder = table_builder.column(col_desc);
         }
 
-        if self.tail_log {
-            if let Some(row) = self.last_row_index(log_file_reader, filtered_entries) {
-                table_builder = table_builder.scroll_to_row(row, Some(Align::BOTTOM));
-            }
+        if self.tail_log && !lines.is_empty() {
+            table_builder = table_builder.scroll_to_row(lines.len() - 1, Some(Align::BOTTOM));
+        } else if let Some(row) = navigated_row {
+            table_builder = table_builder.scroll_to_row(row, None);
         }
 
         if self.sync_line_selection && self.selected_line != viewer_state.selected_line_num {
             if let Some(selected_line) = viewer_state.selected_line_num {
-                if let Some(selected_row) = self.find_row_for_line(selected_line, filtered_entries)
-                {
+                if let Some(selected_row) = lines.row_of(selected_line) {
                     self.selected_line = viewer_state.selected_line_num;
 
                     if !self.tail_log {
@@ -93,13 +371,22 @@ impl LogEntriesTable {
             self.scroll_to_selected = false;
         }
 
+        let mut toggled_run = None;
+        let mut visible_rows: Option<(usize, usize)> = None;
         table_builder
-            .header(24.0, |mut row| {
+            .header(HEADER_HEIGHT, |mut row| {
                 let columns_displayed_count = viewer_state.displayed_columns.len();
                 let mut columns_to_remove: Vec<String> = vec![];
                 let mut from: Option<Arc<String>> = None;
                 let mut to: Option<(String, usize)> = None;
 
+                row.col(|_| {});
+                row.col(|_| {});
+                if self.repeat_collapser.is_active() {
+                    row.col(|ui| {
+                        ui.label(RichText::new("Repeats").strong());
+                    });
+                }
                 for displayed_column in &viewer_state.displayed_columns {
                     row.col(|ui| {
                         ui.dnd_drop_zone::<String, ()>(Frame::default(), |ui| {
@@ -144,7 +431,7 @@ impl LogEntriesTable {
                         });
 
                         if columns_displayed_count > 1 {
-                            if Self::add_tool_button(ui, "❌", "Remove Column").clicked() {
+                            if add_tool_button(ui, "❌", "Remove Column").clicked() {
                                 columns_to_remove.push(displayed_column.clone());
                             }
                         }
@@ -180,16 +467,52 @@ impl LogEntriesTable {
                 }
             })
             .body(|body| {
-                body.rows(16.0, total_rows, |mut row| {
-                    let row_idx = row.index();
-                    let line_number = match filtered_entries {
-                        Some(lines) => lines[row_idx],
-                        None => row_idx,
-                    };
+                body.rows(ROW_HEIGHT, lines.len(), |mut row| {
+                    let row_index = row.index();
+                    let line_number = lines.get(row_index);
+                    visible_rows = Some(match visible_rows {
+                        Some((first, last)) => (first.min(row_index), last.max(row_index)),
+                        None => (row_index, row_index),
+                    });
 
                     row.set_selected(self.selected_line == Some(line_number));
 
-                    Self::ui_logline(log_file_reader, viewer_state, &mut row, line_number);
+                    let row_style = match context_of_hits {
+                        Some(hits) => RowStyle {
+                            dimmed: hits.binary_search(&line_number).is_err(),
+                            separated: row.index() > 0
+                                && lines.get(row.index() - 1) + 1 != line_number,
+                        },
+                        None => RowStyle::default(),
+                    };
+                    row.col(|ui| {
+                        row_style.paint_separator(ui);
+                        Self::ui_gutter(ui, viewer_state, line_number);
+                    });
+                    row.col(|ui| {
+                        row_style.paint_separator(ui);
+                        Self::ui_note_marker(ui, viewer_state, line_number);
+                    });
+                    if self.repeat_collapser.is_active() {
+                        row.col(|ui| {
+                            row_style.paint_separator(ui);
+                            let run = self.repeat_collapser.run_at_row(row_index, line_number);
+                            if let Some(run) = run {
+                                let expanded = self.repeat_collapser.is_expanded(run);
+                                if Self::ui_repeat_badge(ui, log_file_reader, run, expanded) {
+                                    toggled_run = Some(run.first);
+                                }
+                            }
+                        });
+                    }
+                    Self::ui_logline(
+                        log_file_reader,
+                        viewer_state,
+                        &mut row,
+                        line_number,
+                        highlight,
+                        row_style,
+                    );
 
                     if row.response().clicked() {
                         self.selected_line = Some(line_number);
@@ -199,36 +522,123 @@ impl LogEntriesTable {
                     }
                 });
             });
+
+        if is_scrollable {
+            self.time_scroll_bar.ui(ui, body_rect, lines.len());
+        }
+
+        if self.show_timeline {
+            self.viewport = visible_rows.and_then(|(first, last)| {
+                let first_time = Self::first_time(log_file_reader, &lines, first..last + 1)?;
+                let last_time =
+                    Self::first_time(log_file_reader, &lines, (first..last + 1).rev())?;
+                Some((first_time.min(last_time), first_time.max(last_time)))
+            });
+        }
+
+        if let Some(first_line) = toggled_run {
+            self.repeat_collapser.toggle_expanded(first_line);
+        }
+    }
+
+    /// Time of the first of the given rows that has one.
+    fn first_time(
+        log_file_reader: &mut LogFileReader,
+        lines: &TableLines<'_>,
+        mut rows: impl Iterator<Item = usize>,
+    ) -> Option<Timestamp> {
+        rows.find_map(|row| log_file_reader.read_entry(lines.get(row))?.time())
     }
 
-    /// Maps a line number to a table row.
-    /// If there is a set of filtered lines set, a binary search is performed to
-    /// find the correct row. Otherwise, the line number is returned as the row.
-    fn find_row_for_line(
-        &self,
-        line_number: LineNumber,
-        filtered_entries: Option<&[LineNumber]>,
-    ) -> Option<usize> {
-        match filtered_entries {
-            Some(lines) => Some(lines.binary_search(&line_number).ok()?),
-            None => Some(line_number),
+    /// Shows the number of lines folded into a row and the time of the last one.
+    /// Returns true if the badge was clicked, to expand or collapse the run.
+    fn ui_repeat_badge(
+        ui: &mut Ui,
+        log_file_reader: &mut LogFileReader,
+        run: &Run,
+        expanded: bool,
+    ) -> bool {
+        let badge = if expanded {
+            format!("⏷ ×{}", run.len)
+        } else {
+            format!("×{}", run.len)
+        };
+        let last_timestamp = log_file_reader
+            .read_entry(run.last)
+            .map(|log_entry| log_entry.timestamp)
+            .unwrap_or_default();
+
+        let clicked = ui
+            .add(Button::new(RichText::new(badge).monospace()).small())
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_text(if expanded {
+                "Collapse Repeats"
+            } else {
+                "Expand Repeats"
+            })
+            .clicked();
+        if !expanded && !last_timestamp.is_empty() {
+            ui.label(RichText::new(format!("until {}", last_timestamp)).weak());
         }
+        clicked
     }
 
-    fn last_row_index(
-        &self,
-        log_file_reader: &LogFileReader,
-        filtered_entries: Option<&[LineNumber]>,
-    ) -> Option<usize> {
-        match filtered_entries {
-            Some(lines) => {
-                if lines.is_empty() {
-                    None
-                } else {
-                    Some(lines.len() - 1)
-                }
-            }
-            None => Some(log_file_reader.line_count() - 1),
+    /// Shows a marker on bookmarked lines. Clicking the gutter toggles the bookmark.
+    fn ui_gutter(ui: &mut Ui, viewer_state: &LogViewerState, line_num: LineNumber) {
+        let bookmarked = viewer_state.bookmarks.contains(line_num);
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+        let response = response
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_text(if bookmarked {
+                "Remove Bookmark"
+            } else {
+                "Add Bookmark"
+            });
+
+        if bookmarked || response.hovered() {
+            let color = if bookmarked {
+                BOOKMARK_COLOR
+            } else {
+                ui.visuals().weak_text_color()
+            };
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "🔖",
+                FontId::proportional(12.0),
+                color,
+            );
+        }
+        if response.clicked() {
+            viewer_state.bookmarks.toggle(line_num);
+        }
+    }
+
+    /// Shows a marker on annotated lines, with the note as its tooltip.
+    /// Clicking it opens the note editor.
+    fn ui_note_marker(ui: &mut Ui, viewer_state: &mut LogViewerState, line_num: LineNumber) {
+        let note = viewer_state.bookmarks.note(line_num);
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+        let response = response
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_text(note.as_deref().unwrap_or("Add Note"));
+
+        if note.is_some() || response.hovered() {
+            let color = if note.is_some() {
+                NOTE_COLOR
+            } else {
+                ui.visuals().weak_text_color()
+            };
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "📝",
+                FontId::proportional(12.0),
+                color,
+            );
+        }
+        if response.clicked() {
+            viewer_state.note_to_edit = Some(line_num);
         }
     }
 
@@ -237,11 +647,14 @@ impl LogEntriesTable {
         viewer_state: &mut LogViewerState,
         row: &mut TableRow<'_, '_>,
         line_num: LineNumber,
+        highlight: Option<&Regex>,
+        row_style: RowStyle,
     ) -> Option<()> {
         let log_line_opt = log_file_reader.read_line(line_num);
 
         if log_line_opt.is_none() {
             row.col(|ui| {
+                row_style.paint_separator(ui);
                 ui.label(
                     RichText::new("⚠ Failed to read from log file.")
                         .color(ui.visuals().warn_fg_color),
@@ -256,7 +669,8 @@ impl LogEntriesTable {
             Some(log_entry) => {
                 for column_str in &viewer_state.displayed_columns {
                     row.col(|ui| {
-                        let column_value = &log_entry.object[column_str];
+                        row_style.paint_separator(ui);
+                        let column_value = log_entry.field(column_str);
                         let full_col_text = if column_value.is_empty() {
                             String::new()
                         } else {
@@ -277,24 +691,22 @@ impl LogEntriesTable {
                             column_text = column_text.trim();
                         }
 
-                        let mut rich_text = RichText::new(column_text).monospace();
-                        rich_text = match column_style.color {
-                            ColumnTextColor::Color(color) => rich_text.color(color),
-                            ColumnTextColor::BySeverity => rich_text.color(color_from_loglevel(
+                        let text_color = match column_style.color {
+                            ColumnTextColor::Color(color) => color,
+                            ColumnTextColor::BySeverity => color_from_loglevel(
                                 log_entry.object["level"].as_str().unwrap_or("INFO"),
-                            )),
+                            ),
                         };
-                        ui.label(rich_text);
+                        let text_color = row_style.text_color(text_color);
+                        ui.label(highlighted_text(ui, column_text, text_color, highlight));
                     });
                 }
             }
             None => {
                 row.col(|ui| {
-                    ui.label(
-                        RichText::new(log_line.trim())
-                            .monospace()
-                            .color(Color32::WHITE),
-                    );
+                    row_style.paint_separator(ui);
+                    let text_color = row_style.text_color(Color32::WHITE);
+                    ui.label(highlighted_text(ui, log_line.trim(), text_color, highlight));
                 });
             }
         }
@@ -325,6 +737,41 @@ impl LogEntriesTable {
             {
                 self.sync_line_selection = !self.sync_line_selection;
             };
+            if ui
+                .add(Button::new("📊").selected(self.show_timeline))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_text("Show Timeline")
+                .clicked()
+            {
+                self.show_timeline = !self.show_timeline;
+            };
+
+            ui.separator();
+            for level in LogLevel::ALL {
+                let label = format!("{} {}", level.name(), self.level_filter.count(level));
+                if ui
+                    .add(
+                        Button::new(RichText::new(label).color(color_from_loglevel(level.name())))
+                            .selected(self.level_filter.is_shown(level)),
+                    )
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_text(format!("Show {} Lines", level.name()))
+                    .clicked()
+                {
+                    self.level_filter.toggle(level);
+                }
+            }
+
+            ui.separator();
+            let mut collapse_mode = self.repeat_collapser.mode();
+            egui::ComboBox::from_id_source(ui.id().with("collapse_repeats"))
+                .selected_text(collapse_mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in CollapseMode::ALL {
+                        ui.selectable_value(&mut collapse_mode, mode, mode.name());
+                    }
+                });
+            self.repeat_collapser.set_mode(collapse_mode);
 
             add_toolbar_contents(ui);
         });
@@ -332,7 +779,58 @@ impl LogEntriesTable {
     }
 }
 
-fn color_from_loglevel(level: &str) -> Color32 {
+/// A button with a hand cursor and a tooltip, used for the small tool buttons of tabs.
+pub fn add_tool_button(ui: &mut Ui, text: &str, hover_text: &str) -> Response {
+    ui.button(text)
+        .on_hover_cursor(CursorIcon::PointingHand)
+        .on_hover_text(hover_text)
+}
+
+/// Builds monospace text in the given color, with any matches of `highlight`
+/// drawn over a highlighted background.
+pub fn highlighted_text(
+    ui: &Ui,
+    text: &str,
+    color: Color32,
+    highlight: Option<&Regex>,
+) -> WidgetText {
+    let Some(highlight) = highlight.filter(|h| h.is_match(text)) else {
+        return RichText::new(text).monospace().color(color).into();
+    };
+
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let plain_format = TextFormat::simple(font_id.clone(), color);
+    let highlight_format = TextFormat {
+        background: HIGHLIGHT_BACKGROUND,
+        color: Color32::WHITE,
+        ..TextFormat::simple(font_id, color)
+    };
+
+    let mut job = LayoutJob::default();
+    let mut last_end = 0;
+    for mat in highlight.find_iter(text).filter(|m| !m.is_empty()) {
+        job.append(&text[last_end..mat.start()], 0.0, plain_format.clone());
+        job.append(mat.as_str(), 0.0, highlight_format.clone());
+        last_end = mat.end();
+    }
+    job.append(&text[last_end..], 0.0, plain_format);
+
+    job.into()
+}
+
+const ROW_HEIGHT: f32 = 16.0;
+
+const HEADER_HEIGHT: f32 = 24.0;
+
+const GUTTER_WIDTH: f32 = 16.0;
+
+const BOOKMARK_COLOR: Color32 = Color32::from_rgb(90, 160, 255);
+
+const NOTE_COLOR: Color32 = Color32::from_rgb(240, 200, 80);
+
+const HIGHLIGHT_BACKGROUND: Color32 = Color32::from_rgb(140, 100, 0);
+
+pub fn color_from_loglevel(level: &str) -> Color32 {
     match level {
         "ERROR" => Color32::LIGHT_RED,
         "WARNING" => Color32::GOLD,
@@ -1,12 +1,12 @@
-use egui::{
-    Color32, CursorIcon, Response, RichText, Sense,
-};
+use egui::{Color32, RichText, Sense};
 use egui_extras::{Column, TableBuilder};
 use egui_toast::ToastKind;
 
 use super::{
+    field_path::collect_fields,
+    log_entries_table::{add_tool_button, highlighted_text},
     log_file_reader::LogFileReader,
-    log_view::{LogViewerState, LogViewTabTrait},
+    log_view::{LogViewerState, LogViewTabTrait, TabKind},
 };
 
 pub struct LogEntryContextTab {}
@@ -15,16 +15,6 @@ impl LogEntryContextTab {
     pub fn new() -> Box<Self> {
         Box::new(Self {})
     }
-
-    fn add_tool_button(
-        ui: &mut egui::Ui,
-        text: &str,
-        hover_text: &str,
-    ) -> Response {
-        ui.button(text)
-            .on_hover_text(hover_text)
-            .on_hover_cursor(CursorIcon::PointingHand)
-    }
 }
 
 impl LogViewTabTrait for LogEntryContextTab {
@@ -32,6 +22,10 @@ impl LogViewTabTrait for LogEntryContextTab {
         "📓 Context".into()
     }
 
+    fn kind(&self) -> TabKind {
+        TabKind::Context
+    }
+
     fn ui(
         &mut self,
         ui: &mut egui::Ui,
@@ -54,6 +48,8 @@ impl LogViewTabTrait for LogEntryContextTab {
 
         let log_entry = read_log_entry.unwrap();
 
+        let fields = collect_fields(&log_entry.object);
+
         TableBuilder::new(ui)
             .striped(true)
             .min_scrolled_height(0.0)
@@ -66,9 +62,9 @@ impl LogViewTabTrait for LogEntryContextTab {
             .column(Column::auto())
             .column(Column::remainder())
             .body(|mut body| {
-                for entry in log_entry.object.entries() {
-                    let key_str = entry.0;
-                    let value_str = entry.1.to_string();
+                for (key, depth, value) in &fields {
+                    let key_str = key.as_str();
+                    let value_str = value.to_string();
                     let line_count = value_str.chars().filter(|c| *c == '\n').count() + 1;
                     body.row(
                         (line_count as f32) * row_content_height + row_height_padding,
@@ -77,7 +73,7 @@ impl LogViewTabTrait for LogEntryContextTab {
                                 let column_is_shown =
                                     viewer_state.displayed_columns.iter().any(|s| s == key_str);
                                 if !column_is_shown {
-                                    if Self::add_tool_button(ui, "➕", "Add Column").clicked() {
+                                    if add_tool_button(ui, "➕", "Add Column").clicked() {
                                         viewer_state.displayed_columns.push(key_str.to_string());
 
                                         viewer_state.add_toast(
@@ -89,10 +85,11 @@ impl LogViewTabTrait for LogEntryContextTab {
                                 }
                             });
                             row.col(|ui| {
+                                ui.add_space(12.0 * depth.saturating_sub(1) as f32);
                                 ui.label(RichText::new(key_str).color(Color32::WHITE).monospace());
                             });
                             row.col(|ui| {
-                                if Self::add_tool_button(ui, "🗐", "Copy Value").clicked() {
+                                if add_tool_button(ui, "🗐", "Copy Value").clicked() {
                                     ui.output_mut(|o| {
                                         o.copied_text = value_str.clone();
                                     });
@@ -105,7 +102,13 @@ impl LogViewTabTrait for LogEntryContextTab {
                                 }
                             });
                             row.col(|ui| {
-                                ui.label(RichText::new(value_str.trim()).monospace());
+                                let text_color = ui.visuals().text_color();
+                                ui.label(highlighted_text(
+                                    ui,
+                                    value_str.trim(),
+                                    text_color,
+                                    viewer_state.search_highlight.as_ref(),
+                                ));
                             });
                         },
                     );
@@ -1,17 +1,24 @@
 use io::Error;
 use std::{
+    fmt::{self, Display},
     fs::File,
     io::{self, BufReader, Read, Seek, SeekFrom},
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 use std::time::SystemTime;
 use crossbeam_channel::Receiver;
 
-use grep::searcher::{Searcher, Sink, SinkMatch};
+use grep::searcher::{Searcher, SearcherBuilder, Sink, SinkMatch};
 use grep_regex::RegexMatcher;
 use json::JsonValue;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 
+use super::field_path::lookup_field;
+use super::text_encoding::TextEncoding;
+use super::timestamp::{parse_timestamp, Timestamp};
+
 fn to_io_error(err: notify::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
@@ -22,9 +29,128 @@ pub struct LogEntry {
     pub object: JsonValue,
 }
 
+impl LogEntry {
+    /// Returns the value of a field by top-level key, dotted path or JSON Pointer.
+    /// Missing fields are returned as `JsonValue::Null`.
+    pub fn field(&self, key: &str) -> &JsonValue {
+        lookup_field(&self.object, key)
+    }
+
+    /// Parses the entry's timestamp, if it is in a recognized format.
+    pub fn time(&self) -> Option<Timestamp> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
+/// Reason a line could not be parsed as a log entry.
+#[derive(Debug)]
+pub enum ParseError {
+    Json(json::Error),
+    NotAnObject,
+    MissingTimestamp,
+}
+
+impl ParseError {
+    /// Character position within the line at which parsing failed, if known.
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            ParseError::Json(json::Error::UnexpectedCharacter { column, .. }) => Some(*column),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Json(json::Error::UnexpectedCharacter { ch, .. }) => {
+                write!(f, "Unexpected character: {:?}", ch)
+            }
+            ParseError::Json(json::Error::UnexpectedEndOfJson) => {
+                f.write_str("Unexpected end of JSON (empty or truncated line)")
+            }
+            ParseError::Json(e) => write!(f, "{}", e),
+            ParseError::NotAnObject => f.write_str("Line is valid JSON but not an object"),
+            ParseError::MissingTimestamp => f.write_str("Missing string field \"t\""),
+        }
+    }
+}
+
 pub type LineNumber = usize;
 
-type FileOffset = u64;
+pub type FileOffset = u64;
+
+/// A snapshot of the line positions of a log file at the time it was loaded.
+/// Cheap to clone, and can be sent to other threads to read lines independently
+/// of the `LogFileReader` that created it.
+#[derive(Clone)]
+pub struct LineIndex {
+    path: PathBuf,
+    encoding: TextEncoding,
+    line_map: Arc<Vec<FileOffset>>,
+}
+
+impl LineIndex {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_map.len().saturating_sub(1)
+    }
+
+    /// Size of the file in bytes at the time it was loaded.
+    pub fn file_size(&self) -> FileOffset {
+        self.line_map.last().copied().unwrap_or_default()
+    }
+
+    /// True if this is a later load of the same file as `earlier`, in which the first
+    /// `line_count` lines of `earlier` are unchanged and only lines were appended after them.
+    pub fn extends(&self, earlier: &LineIndex, line_count: LineNumber) -> bool {
+        self.path == earlier.path
+            && self.encoding == earlier.encoding
+            && line_count <= self.line_count()
+            && earlier.line_start_offset(line_count) == self.line_start_offset(line_count)
+    }
+
+    /// Returns the file offset of the beginning of the given line number,
+    /// or None if the line number is past the end of the index.
+    pub fn line_start_offset(&self, line_num: LineNumber) -> Option<FileOffset> {
+        self.line_map.get(line_num).copied()
+    }
+
+    /// Calls `f` with each decoded line in the given range in order.
+    /// Stops early if `f` returns false.
+    pub fn for_each_line(
+        &self,
+        lines: Range<LineNumber>,
+        mut f: impl FnMut(LineNumber, &str) -> bool,
+    ) -> io::Result<()> {
+        let end = lines.end.min(self.line_count());
+        if lines.start >= end {
+            return Ok(());
+        }
+
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(self.line_map[lines.start]))?;
+
+        let mut line_bytes = vec![];
+        for line_num in lines.start..end {
+            let line_len = self.line_map[line_num + 1] - self.line_map[line_num];
+            line_bytes.resize(line_len as usize, 0);
+            reader.read_exact(&mut line_bytes)?;
+
+            if !f(line_num, &self.encoding.decode(&line_bytes)) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
 
 struct AbsolutePositionSink<F>(pub F)
 where
@@ -42,9 +168,11 @@ where
 }
 
 pub struct LogFileReader {
+    path: PathBuf,
     buf_reader: BufReader<File>,
-    line_map: Vec<FileOffset>,
+    line_map: Arc<Vec<FileOffset>>,
     file_size: FileOffset,
+    encoding: TextEncoding,
     load_time_point: Option<SystemTime>,
     _watcher: Box<dyn Watcher>,
     watcher_recv: Receiver<notify::Result<Event>>,
@@ -60,11 +188,18 @@ impl LogFileReader {
             .watch(path, RecursiveMode::NonRecursive)
             .map_err(to_io_error)?;
 
-        let file = File::open(path)?;
+        let mut file = File::open(path)?;
+
+        let mut bom = [0u8; 3];
+        let bom_len = file.read(&mut bom)?;
+        let encoding = TextEncoding::from_bom(&bom[..bom_len]).unwrap_or_default();
+
         Ok(LogFileReader {
+            path: path.to_owned(),
             buf_reader: BufReader::new(file),
-            line_map: Vec::new(),
+            line_map: Default::default(),
             file_size: 0,
+            encoding,
             load_time_point: None,
             _watcher: Box::new(watcher),
             watcher_recv: rx,
@@ -78,29 +213,95 @@ impl LogFileReader {
         puffin::profile_function!();
 
         self.buf_reader.rewind()?;
-        self.line_map.clear();
 
+        let mut line_map = vec![];
+        if self.encoding.code_unit_size() == 1 {
+            self.load_line_map_bytes(&mut line_map)?;
+        } else {
+            self.load_line_map_code_units(&mut line_map)?;
+        }
+
+        self.buf_reader.seek(SeekFrom::End(0))?;
+        self.file_size = self.buf_reader.stream_position()?;
+        line_map.push(self.file_size);
+        self.line_map = Arc::new(line_map);
+
+        self.load_time_point = Some(SystemTime::now());
+        Ok(self.line_count())
+    }
+
+    /// Populates the line map using grep, for encodings where a newline is a single byte.
+    fn load_line_map_bytes(&mut self, line_map: &mut Vec<FileOffset>) -> io::Result<()> {
         // Build a grep matcher and searcher matching the options
         let newline = "$";
         let matcher = RegexMatcher::new_line_matcher(&newline).unwrap();
-        let mut searcher = Searcher::new();
+        // Offsets must refer to the raw file, so don't let grep transcode on finding a BOM.
+        let mut searcher = SearcherBuilder::new().bom_sniffing(false).build();
 
         // Load all newline file positions into line_map
         searcher.search_reader(
             matcher,
             self.buf_reader.get_ref(),
             AbsolutePositionSink(|file_offset| -> Result<bool, Error> {
-                self.line_map.push(file_offset as FileOffset);
+                line_map.push(file_offset as FileOffset);
                 Ok(true)
             }),
-        )?;
+        )
+    }
 
-        self.buf_reader.seek(SeekFrom::End(0))?;
-        self.file_size = self.buf_reader.stream_position()?;
-        self.line_map.push(self.file_size);
+    /// Populates the line map by scanning for newline code units, for encodings
+    /// where a newline spans multiple bytes.
+    fn load_line_map_code_units(&mut self, line_map: &mut Vec<FileOffset>) -> io::Result<()> {
+        let code_unit_size = self.encoding.code_unit_size();
+        let mut chunk = vec![0u8; 64 * 1024];
+        let mut chunk_offset: FileOffset = 0;
+        let mut pending_line_start = Some(0);
 
-        self.load_time_point = Some(SystemTime::now());
-        Ok(self.line_count())
+        loop {
+            // Keep chunks aligned to code units so none straddle two reads.
+            let mut chunk_len = 0;
+            while chunk_len < chunk.len() {
+                match self.buf_reader.read(&mut chunk[chunk_len..])? {
+                    0 => break,
+                    n => chunk_len += n,
+                }
+            }
+            if chunk_len < code_unit_size {
+                break;
+            }
+
+            for (i, code_unit) in chunk[..chunk_len].chunks_exact(code_unit_size).enumerate() {
+                if let Some(line_start) = pending_line_start.take() {
+                    line_map.push(line_start);
+                }
+                if self.encoding.is_newline(code_unit) {
+                    pending_line_start =
+                        Some(chunk_offset + ((i + 1) * code_unit_size) as FileOffset);
+                }
+            }
+            chunk_offset += chunk_len as FileOffset;
+        }
+
+        Ok(())
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Returns a snapshot of the currently loaded line positions.
+    pub fn line_index(&self) -> LineIndex {
+        LineIndex {
+            path: self.path.clone(),
+            encoding: self.encoding,
+            line_map: self.line_map.clone(),
+        }
+    }
+
+    /// Changes the encoding the file is decoded with and reloads it.
+    pub fn set_encoding(&mut self, encoding: TextEncoding) -> io::Result<usize> {
+        self.encoding = encoding;
+        self.load()
     }
 
     pub fn has_changed(&mut self) -> bool {
@@ -117,7 +318,7 @@ impl LogFileReader {
         self.line_map.len() - 1
     }
 
-    /// Reads a line from the file parsed as a UTF8 string
+    /// Reads a line from the file decoded to a string using the file's encoding
     pub fn read_line(&mut self, line_num: LineNumber) -> Option<String> {
         let (file_start_offset, file_end_offset) = self.line_file_offsets(line_num);
         self.buf_reader
@@ -128,7 +329,7 @@ impl LogFileReader {
         let mut line_bytes: Vec<u8> = vec![0u8; (file_end_offset - file_start_offset) as usize];
         self.buf_reader.read_exact(&mut line_bytes).ok()?;
 
-        Some(String::from_utf8_lossy(&line_bytes).to_string())
+        Some(self.encoding.decode(&line_bytes))
     }
 
     /// Reads a log entry from the give line from the file.
@@ -142,15 +343,23 @@ impl LogFileReader {
     /// Format is <json-object>\n
     /// e.g. { "t": "2023-06-25T00:49:20Z", "message": "hello, world" }
     pub fn parse_logline(line: &str) -> Option<LogEntry> {
-        let log_entry = json::parse(line).ok()?;
+        Self::try_parse_logline(line).ok()
+    }
+
+    /// Like `parse_logline`, but reports why the line could not be parsed.
+    pub fn try_parse_logline(line: &str) -> Result<LogEntry, ParseError> {
+        let log_entry = json::parse(line).map_err(ParseError::Json)?;
 
         if log_entry.is_object() {
-            Some(LogEntry {
-                timestamp: log_entry["t"].as_str()?.to_owned(),
+            Ok(LogEntry {
+                timestamp: log_entry["t"]
+                    .as_str()
+                    .ok_or(ParseError::MissingTimestamp)?
+                    .to_owned(),
                 object: log_entry,
             })
         } else {
-            None
+            Err(ParseError::NotAnObject)
         }
     }
 
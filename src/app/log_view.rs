@@ -1,22 +1,45 @@
 use std::collections::HashMap;
 use std::default::Default;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use std::{
     io,
     path::{Path, PathBuf},
 };
 
-use egui::{Align2, Color32, Direction, Id, Ui, WidgetText};
-use egui_dock::{DockArea, DockState, NodeIndex, SurfaceIndex, TabViewer};
+use egui::{Align2, Color32, Direction, Id, RichText, Ui, WidgetText};
+use egui_dock::{DockArea, DockState, NodeIndex, SurfaceIndex, TabIndex, TabViewer};
 use egui_toast::{Toast, ToastKind, ToastOptions, Toasts};
 use log::{error, info};
+use regex::Regex;
 
 use super::log_file_reader::LogFileReader;
 use super::{
+    bookmarks::{Bookmarks, SharedBookmarkStore},
+    bookmarks_tab::BookmarksTab,
+    field_path::collect_fields,
+    fields::FieldIndex,
+    fields_tab::FieldsTab,
+    filter_chain_tab::{FilterChainTab, FilterStage},
     filtered_log_entries_tab::FilteredLogEntriesTab,
+    go_to_dialog::GoToDialog,
+    jq_tab::JqTab,
+    key_bindings::Command,
+    level_filter::LevelIndex,
+    log_analysis::BackgroundAnalysis,
     log_entries_tab::LogEntriesTab,
     log_entry_context_tab::LogEntryContextTab,
-    log_file_reader::{LineNumber},
+    log_file_reader::{LineIndex, LineNumber},
+    notes::{export_incident_timeline, NoteEditor},
+    notes_tab::NotesTab,
+    parse_diagnostics_tab::{ParseDiagnostics, ParseDiagnosticsTab},
+    patterns::PatternMiner,
+    patterns_tab::PatternsTab,
+    repeats::MessageIndex,
+    search_library::SharedSearchLibrary,
+    stats_tab::{LogStats, StatsTab},
+    text_encoding::TextEncoding,
+    time_histogram::VolumeIndex,
+    time_range::{TimeRangeFilter, TimestampOrder},
 };
 
 #[derive(Default)]
@@ -36,6 +59,37 @@ pub struct LogViewerState {
     pub displayed_columns: Vec<String>,
     pub column_styles: HashMap<String, ColumnStyle>,
     pub toasts: Toasts,
+    pub parse_diagnostics: BackgroundAnalysis<ParseDiagnostics>,
+    /// Severity of every line, for the level filter toggles of the log tables.
+    pub log_levels: BackgroundAnalysis<LevelIndex>,
+    pub timestamp_order: BackgroundAnalysis<TimestampOrder>,
+    /// Message of every line, for collapsing repeats.
+    /// Only started once a table collapses repeats.
+    pub message_index: BackgroundAnalysis<MessageIndex>,
+    /// Message templates of every line. Only started once patterns are shown.
+    pub patterns: BackgroundAnalysis<PatternMiner>,
+    /// Entries per second and level, for the timelines of the log tables.
+    /// Only started once a timeline is shown.
+    pub time_volume: BackgroundAnalysis<VolumeIndex>,
+    /// Summary statistics of the file. Only started once stats are shown.
+    pub stats: BackgroundAnalysis<LogStats>,
+    /// Fields of every entry. Only started once fields are shown.
+    pub fields: BackgroundAnalysis<FieldIndex>,
+    /// Restricts every table of the view to a time range.
+    pub time_range: TimeRangeFilter,
+    /// Matches of the most recently executed search, highlighted in the Log and Context tabs.
+    pub search_highlight: Option<Regex>,
+    /// Keyboard navigation for the tab currently being drawn, if it's the one navigated.
+    pub navigation: Option<Navigation>,
+    /// Set to move keyboard focus to the search box of the active search tab.
+    pub focus_search: bool,
+    /// Search history and saved searches, shared with the other open files.
+    pub search_library: SharedSearchLibrary,
+    pub bookmarks: Bookmarks,
+    /// Set to open the note editor for a line.
+    pub note_to_edit: Option<LineNumber>,
+    /// Stages to add to the Filters tab, which is opened if there's none.
+    pub filters_to_add: Vec<FilterStage>,
 }
 
 impl LogViewerState {
@@ -46,6 +100,58 @@ impl LogViewerState {
             options: ToastOptions::default().duration_in_seconds(duration_in_seconds),
         });
     }
+
+    pub fn select_next_malformed_line(&mut self) {
+        let next_line = self
+            .parse_diagnostics
+            .lock()
+            .next_malformed_line(self.selected_line_num);
+        match next_line {
+            Some(line_num) => self.selected_line_num = Some(line_num),
+            None => self.add_toast(ToastKind::Info, "No more malformed lines.".into(), 2.0),
+        }
+    }
+
+    pub fn select_previous_malformed_line(&mut self) {
+        let previous_line = self
+            .parse_diagnostics
+            .lock()
+            .previous_malformed_line(self.selected_line_num);
+        match previous_line {
+            Some(line_num) => self.selected_line_num = Some(line_num),
+            None => self.add_toast(ToastKind::Info, "No more malformed lines.".into(), 2.0),
+        }
+    }
+
+    pub fn toggle_bookmark(&mut self) {
+        let Some(line_num) = self.selected_line_num else {
+            self.add_toast(ToastKind::Info, "Select a line to bookmark.".into(), 2.0);
+            return;
+        };
+        self.bookmarks.toggle(line_num);
+    }
+
+    /// Opens the note editor for the selected line.
+    pub fn edit_note(&mut self) {
+        match self.selected_line_num {
+            Some(line_num) => self.note_to_edit = Some(line_num),
+            None => self.add_toast(ToastKind::Info, "Select a line to annotate.".into(), 2.0),
+        }
+    }
+
+    pub fn select_next_bookmark(&mut self) {
+        match self.bookmarks.next(self.selected_line_num) {
+            Some(line_num) => self.selected_line_num = Some(line_num),
+            None => self.add_toast(ToastKind::Info, "No more bookmarks.".into(), 2.0),
+        }
+    }
+
+    pub fn select_previous_bookmark(&mut self) {
+        match self.bookmarks.previous(self.selected_line_num) {
+            Some(line_num) => self.selected_line_num = Some(line_num),
+            None => self.add_toast(ToastKind::Info, "No more bookmarks.".into(), 2.0),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -102,6 +208,22 @@ impl Default for LogViewerState {
             toasts: Toasts::new()
                 .anchor(Align2::CENTER_BOTTOM, (0.0, -25.0))
                 .direction(Direction::BottomUp),
+            parse_diagnostics: Default::default(),
+            log_levels: Default::default(),
+            timestamp_order: Default::default(),
+            message_index: Default::default(),
+            patterns: Default::default(),
+            time_volume: Default::default(),
+            stats: Default::default(),
+            fields: Default::default(),
+            time_range: Default::default(),
+            search_highlight: None,
+            navigation: None,
+            focus_search: false,
+            search_library: Default::default(),
+            bookmarks: Default::default(),
+            note_to_edit: None,
+            filters_to_add: vec![],
         }
     }
 }
@@ -117,8 +239,34 @@ impl Default for &'static ColumnStyle {
     }
 }
 
+/// A keyboard navigation command, handled by the tab it's delivered to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Navigation {
+    PreviousLine,
+    NextLine,
+    PreviousPage,
+    NextPage,
+    FirstLine,
+    LastLine,
+    ToggleTail,
+    NextSearchHit,
+    PreviousSearchHit,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TabKind {
+    Log,
+    Context,
+    Search,
+    Filters,
+    Other,
+}
+
 pub trait LogViewTabTrait {
     fn title(&self) -> egui::WidgetText;
+    fn kind(&self) -> TabKind {
+        TabKind::Other
+    }
     fn ui(
         &mut self,
         ui: &mut Ui,
@@ -134,6 +282,8 @@ pub struct LogView {
     tree: DockState<Box<dyn LogViewTabTrait>>,
     log_view_context: LogViewContext,
     file_path: PathBuf,
+    go_to_dialog: Option<GoToDialog>,
+    note_editor: Option<NoteEditor>,
 }
 
 struct LogViewContext {
@@ -141,6 +291,9 @@ struct LogViewContext {
     log_file_reader: LogFileReader,
     tabs_to_open: Vec<(Box<dyn LogViewTabTrait>, SurfaceIndex, NodeIndex)>,
     viewer_state: LogViewerState,
+    /// Navigation read from the keyboard this frame, and the tab it's for.
+    pending_navigation: Option<Navigation>,
+    navigation_target: Option<Id>,
 }
 
 impl TabViewer for LogViewContext {
@@ -151,7 +304,19 @@ impl TabViewer for LogViewContext {
     }
 
     fn ui(&mut self, ui: &mut Ui, tab: &mut Self::Tab) {
+        let is_navigated = match self.pending_navigation {
+            Some(Navigation::NextSearchHit | Navigation::PreviousSearchHit) => {
+                tab.kind() == TabKind::Search
+            }
+            Some(_) => self.navigation_target == Some(Id::new(tab as *const _)),
+            None => false,
+        };
+        if is_navigated {
+            self.viewer_state.navigation = self.pending_navigation.take();
+        }
+
         tab.ui(ui, &mut self.log_file_reader, &mut self.viewer_state);
+        self.viewer_state.navigation = None;
     }
 
     fn id(&mut self, tab: &mut Self::Tab) -> Id {
@@ -173,11 +338,64 @@ impl TabViewer for LogViewContext {
             self.tabs_to_open
                 .push((FilteredLogEntriesTab::new(self.log_file_path.clone()), surface_index, node));
         }
+        if ui.button("Bookmarks").clicked() {
+            self.tabs_to_open
+                .push((BookmarksTab::new(), surface_index, node));
+        }
+        if ui.button("Notes").clicked() {
+            self.tabs_to_open.push((NotesTab::new(), surface_index, node));
+        }
+        if ui.button("Filters").clicked() {
+            self.tabs_to_open
+                .push((FilterChainTab::new(), surface_index, node));
+        }
+        if ui.button("jq").clicked() {
+            self.tabs_to_open.push((JqTab::new(), surface_index, node));
+        }
+        if ui.button("Patterns").clicked() {
+            self.tabs_to_open.push((PatternsTab::new(), surface_index, node));
+        }
+        if ui.button("Stats").clicked() {
+            self.tabs_to_open.push((StatsTab::new(), surface_index, node));
+        }
+        if ui.button("Fields").clicked() {
+            self.tabs_to_open.push((FieldsTab::new(), surface_index, node));
+        }
+        if ui.button("Parse Errors").clicked() {
+            self.tabs_to_open
+                .push((ParseDiagnosticsTab::new(), surface_index, node));
+        }
+
+        let saved_searches = self.viewer_state.search_library.borrow().saved().to_vec();
+        if !saved_searches.is_empty() {
+            ui.separator();
+            ui.weak("Saved Searches");
+            for saved_search in saved_searches {
+                if ui
+                    .button(&saved_search.name)
+                    .on_hover_text(saved_search.params.summary())
+                    .clicked()
+                {
+                    self.tabs_to_open.push((
+                        FilteredLogEntriesTab::with_search(
+                            self.log_file_path.clone(),
+                            saved_search.params,
+                        ),
+                        surface_index,
+                        node,
+                    ));
+                }
+            }
+        }
     }
 }
 
 impl LogView {
-    pub fn open(file_path: &Path) -> io::Result<Self> {
+    pub fn open(
+        file_path: &Path,
+        search_library: SharedSearchLibrary,
+        bookmark_store: SharedBookmarkStore,
+    ) -> io::Result<Self> {
         let mut tree: DockState<Box<dyn LogViewTabTrait>> =
             DockState::new(vec![LogEntriesTab::new()]);
         let new_nodes = tree.main_surface_mut().split_below(
@@ -194,7 +412,9 @@ impl LogView {
         Ok(LogView {
             tree,
             file_path: file_path.to_owned(),
-            log_view_context: LogViewContext::open(file_path)?,
+            log_view_context: LogViewContext::open(file_path, search_library, bookmark_store)?,
+            go_to_dialog: None,
+            note_editor: None,
         })
     }
 
@@ -217,12 +437,42 @@ impl LogView {
             }
         }
 
+        let log_file_reader = &mut self.log_view_context.log_file_reader;
+        let viewer_state = &mut self.log_view_context.viewer_state;
+        viewer_state.parse_diagnostics.sync(log_file_reader);
+        viewer_state.log_levels.sync(log_file_reader);
+        viewer_state.timestamp_order.sync(log_file_reader);
+        if !viewer_state.parse_diagnostics.is_complete()
+            || !viewer_state.log_levels.is_complete()
+            || !viewer_state.timestamp_order.is_complete()
+        {
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+        viewer_state
+            .time_range
+            .update(ui, log_file_reader, &viewer_state.timestamp_order);
+
+        egui::TopBottomPanel::top(Id::new(&self.file_path).with("time_range"))
+            .show_inside(ui, |ui| self.log_view_context.time_range_ui(ui));
+
+        egui::TopBottomPanel::bottom(Id::new(&self.file_path).with("status_bar"))
+            .show_inside(ui, |ui| self.log_view_context.status_bar_ui(ui));
+
         DockArea::new(&mut self.tree)
             .id(Id::new(&self.file_path))
             .show_add_buttons(true)
             .show_add_popup(true)
             .show_inside(ui, &mut self.log_view_context);
 
+        if !self.log_view_context.viewer_state.filters_to_add.is_empty()
+            && !self
+                .tree
+                .iter_all_tabs()
+                .any(|(_, tab)| tab.kind() == TabKind::Filters)
+        {
+            self.log_view_context.open_tab(FilterChainTab::new());
+        }
+
         for (tab_type, destination_surface, destination_node) in
             self.log_view_context.tabs_to_open.drain(..)
         {
@@ -231,23 +481,243 @@ impl LogView {
             self.tree.push_to_focused_leaf(tab_type);
         }
 
+        if let Some(ref mut go_to_dialog) = self.go_to_dialog {
+            let context = &mut self.log_view_context;
+            let (open, selected_line) = go_to_dialog.show(
+                ui.ctx(),
+                Id::new(&self.file_path).with("go_to"),
+                &mut context.log_file_reader,
+                &context.viewer_state.timestamp_order,
+            );
+            if selected_line.is_some() {
+                context.viewer_state.selected_line_num = selected_line;
+            }
+            if !open {
+                self.go_to_dialog = None;
+            }
+        }
+
+        let bookmarks = &self.log_view_context.viewer_state.bookmarks;
+        if let Some(line_num) = self.log_view_context.viewer_state.note_to_edit.take() {
+            self.note_editor = Some(NoteEditor::new(line_num, bookmarks));
+        }
+        if let Some(ref mut note_editor) = self.note_editor {
+            if !note_editor.show(ui.ctx(), Id::new(&self.file_path).with("note"), bookmarks) {
+                self.note_editor = None;
+            }
+        }
+
         self.log_view_context.viewer_state.toasts.show(ui.ctx());
     }
 
     pub fn open_search(&mut self) {
         self.log_view_context.open_search()
     }
+
+    /// Runs a command bound to keys or picked from the command palette.
+    /// Commands that aren't about a single view are ignored.
+    pub fn run_command(&mut self, command: Command) {
+        let navigation = match command {
+            Command::NewSearchTab => {
+                self.open_search();
+                return;
+            }
+            Command::FocusSearch => {
+                if !self.focus_tab(TabKind::Search) {
+                    self.open_search();
+                }
+                self.log_view_context.viewer_state.focus_search = true;
+                return;
+            }
+            Command::GoTo => {
+                self.open_go_to();
+                return;
+            }
+            Command::FocusContext => {
+                self.focus_tab(TabKind::Context);
+                return;
+            }
+            Command::FocusLog => {
+                self.focus_tab(TabKind::Log);
+                return;
+            }
+            Command::ToggleBookmark => {
+                self.log_view_context.viewer_state.toggle_bookmark();
+                return;
+            }
+            Command::NextBookmark => {
+                self.log_view_context.viewer_state.select_next_bookmark();
+                return;
+            }
+            Command::PreviousBookmark => {
+                self.log_view_context.viewer_state.select_previous_bookmark();
+                return;
+            }
+            Command::EditNote => {
+                self.log_view_context.viewer_state.edit_note();
+                return;
+            }
+            Command::ExportTimeline => {
+                let context = &mut self.log_view_context;
+                export_incident_timeline(&mut context.log_file_reader, &mut context.viewer_state);
+                return;
+            }
+            Command::PreviousLine => Navigation::PreviousLine,
+            Command::NextLine => Navigation::NextLine,
+            Command::PreviousPage => Navigation::PreviousPage,
+            Command::NextPage => Navigation::NextPage,
+            Command::FirstLine => Navigation::FirstLine,
+            Command::LastLine => Navigation::LastLine,
+            Command::ToggleTail => Navigation::ToggleTail,
+            Command::NextSearchHit => Navigation::NextSearchHit,
+            Command::PreviousSearchHit => Navigation::PreviousSearchHit,
+            _ => return,
+        };
+
+        // Navigate the focused tab, or the Log tab if the focused tab doesn't show a log table.
+        let focused_tab = self
+            .tree
+            .find_active_focused()
+            .map(|(_, tab)| (Id::new(tab as *const _), tab.kind()));
+        self.log_view_context.navigation_target = match focused_tab {
+            Some((id, kind)) if kind != TabKind::Context => Some(id),
+            _ => self
+                .tree
+                .iter_all_tabs()
+                .find(|(_, tab)| tab.kind() == TabKind::Log)
+                .map(|(_, tab)| Id::new(tab as *const _)),
+        };
+        self.log_view_context.pending_navigation = Some(navigation);
+    }
+
+    /// Makes the first tab of the given kind active and focused.
+    /// Returns false if there is no such tab.
+    fn focus_tab(&mut self, kind: TabKind) -> bool {
+        let tab_location = self.tree.iter_surfaces().enumerate().find_map(|(surface, s)| {
+            s.node_tree()?.iter().enumerate().find_map(|(node, n)| {
+                let tab = n.tabs()?.iter().position(|tab| tab.kind() == kind)?;
+                Some((SurfaceIndex(surface), NodeIndex(node), TabIndex(tab)))
+            })
+        });
+        let Some((surface, node, tab)) = tab_location else {
+            return false;
+        };
+        self.tree.set_active_tab((surface, node, tab));
+        self.tree.set_focused_node_and_surface((surface, node));
+        true
+    }
+
+    /// True while the go-to dialog or the note editor is open.
+    pub fn has_dialog_open(&self) -> bool {
+        self.go_to_dialog.is_some() || self.note_editor.is_some()
+    }
+
+    /// Opens the dialog for jumping to a line number or a timestamp.
+    pub fn open_go_to(&mut self) {
+        self.go_to_dialog = Some(GoToDialog::new());
+    }
+
+    pub fn line_index(&self) -> LineIndex {
+        self.log_view_context.log_file_reader.line_index()
+    }
+
+    /// The displayed columns of a line joined into one line of text, or the raw
+    /// line if it isn't a log entry.
+    pub fn line_summary(&mut self, line_num: LineNumber) -> Option<String> {
+        let context = &mut self.log_view_context;
+        let line = context.log_file_reader.read_line(line_num)?;
+        let Some(log_entry) = LogFileReader::parse_logline(&line) else {
+            return Some(line.trim().to_string());
+        };
+        let columns: Vec<String> = context
+            .viewer_state
+            .displayed_columns
+            .iter()
+            .map(|column| log_entry.field(column))
+            .filter(|value| !value.is_null())
+            .map(|value| value.to_string())
+            .collect();
+        Some(columns.join("  "))
+    }
+
+    /// Message templates of the file, mined in the background from the first call on.
+    pub fn patterns(&mut self) -> &BackgroundAnalysis<PatternMiner> {
+        let viewer_state = &mut self.log_view_context.viewer_state;
+        viewer_state
+            .patterns
+            .sync(&self.log_view_context.log_file_reader);
+        &viewer_state.patterns
+    }
+
+    /// Selects a line in the tables of the view.
+    pub fn select_line(&mut self, line_num: LineNumber) {
+        self.log_view_context.viewer_state.selected_line_num = Some(line_num);
+    }
+
+    pub fn displayed_columns(&self) -> &[String] {
+        &self.log_view_context.viewer_state.displayed_columns
+    }
+
+    /// Fields of the selected entry, or of the first entry if none is selected,
+    /// that aren't displayed as columns yet. Nested fields are listed by their dotted path.
+    pub fn available_columns(&mut self) -> Vec<String> {
+        let context = &mut self.log_view_context;
+        let line_num = context.viewer_state.selected_line_num.unwrap_or(0);
+        let Some(log_entry) = context.log_file_reader.read_entry(line_num) else {
+            return vec![];
+        };
+        collect_fields(&log_entry.object)
+            .into_iter()
+            .map(|(key, _, _)| key)
+            .filter(|key| !context.viewer_state.displayed_columns.contains(key))
+            .collect()
+    }
+
+    pub fn add_column(&mut self, column: String) {
+        let viewer_state = &mut self.log_view_context.viewer_state;
+        let text = format!("Added column '{}'", column);
+        viewer_state.displayed_columns.push(column);
+        viewer_state.add_toast(ToastKind::Info, text.into(), 2.0);
+    }
+
+    pub fn remove_column(&mut self, column: &str) {
+        let viewer_state = &mut self.log_view_context.viewer_state;
+        if viewer_state.displayed_columns.len() > 1 {
+            viewer_state.displayed_columns.retain(|c| c != column);
+            viewer_state.add_toast(ToastKind::Info, "Removed column.".into(), 2.0);
+        } else {
+            viewer_state.add_toast(ToastKind::Warning, "Can't remove the last column.".into(), 2.0);
+        }
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        self.log_view_context.log_file_reader.encoding()
+    }
+
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.log_view_context.set_encoding(encoding)
+    }
 }
 
 impl LogViewContext {
-    pub fn open(filepath: &Path) -> io::Result<Self> {
+    pub fn open(
+        filepath: &Path,
+        search_library: SharedSearchLibrary,
+        bookmark_store: SharedBookmarkStore,
+    ) -> io::Result<Self> {
         puffin::profile_function!();
 
         let mut log_view = LogViewContext {
             log_file_path: filepath.to_owned(),
             log_file_reader: LogFileReader::open(filepath)?,
             tabs_to_open: vec![],
-            viewer_state: Default::default(),
+            viewer_state: LogViewerState {
+                search_library,
+                bookmarks: Bookmarks::new(bookmark_store, filepath),
+                ..Default::default()
+            },
+            pending_navigation: None,
+            navigation_target: None,
         };
 
         let load_start_time = SystemTime::now();
@@ -276,14 +746,68 @@ impl LogViewContext {
         Ok(log_view)
     }
 
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        match self.log_file_reader.set_encoding(encoding) {
+            Ok(line_count) => {
+                self.viewer_state.add_toast(
+                    ToastKind::Info,
+                    format!("Reloaded {} lines as {}.", line_count, encoding.name()).into(),
+                    5.0,
+                );
+            }
+            Err(e) => {
+                self.viewer_state.add_toast(
+                    ToastKind::Error,
+                    format!("Failed to reload file as {}: {}", encoding.name(), e).into(),
+                    10.0,
+                );
+            }
+        }
+    }
+
     pub fn open_search(&mut self) {
+        self.open_tab(FilteredLogEntriesTab::new(self.log_file_path.clone()));
+    }
+
+    fn open_tab(&mut self, tab: Box<dyn LogViewTabTrait>) {
         let dest_surface = SurfaceIndex::main();
         let dest_node = NodeIndex::root().right();
 
-        self.tabs_to_open.push((
-            FilteredLogEntriesTab::new(self.log_file_path.clone()),
-            dest_surface,
-            dest_node,
-        ));
+        self.tabs_to_open.push((tab, dest_surface, dest_node));
+    }
+
+    fn time_range_ui(&mut self, ui: &mut Ui) {
+        self.viewer_state.time_range.ui(
+            ui,
+            &mut self.log_file_reader,
+            &self.viewer_state.timestamp_order,
+        );
+    }
+
+    fn status_bar_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{} lines", self.log_file_reader.line_count()));
+            ui.separator();
+            ui.label(self.log_file_reader.encoding().name());
+            ui.separator();
+
+            let parse_diagnostics = &self.viewer_state.parse_diagnostics;
+            let malformed_count = parse_diagnostics.lock().malformed_lines().len();
+            if !parse_diagnostics.is_complete() {
+                ui.spinner();
+            }
+            if malformed_count == 0 {
+                ui.label("No malformed lines");
+            } else if ui
+                .link(
+                    RichText::new(format!("⚠ {} malformed lines", malformed_count))
+                        .color(ui.visuals().warn_fg_color),
+                )
+                .on_hover_text("Show Parse Errors")
+                .clicked()
+            {
+                self.open_tab(ParseDiagnosticsTab::new());
+            }
+        });
     }
 }
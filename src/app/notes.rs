@@ -0,0 +1,219 @@
+use std::fs;
+
+use egui::{Id, RichText};
+use egui_toast::ToastKind;
+use json::JsonValue;
+use rfd::FileDialog;
+
+use super::{
+    bookmarks::Bookmarks,
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::LogViewerState,
+    timestamp::Timestamp,
+};
+
+/// Window for writing the note attached to a line.
+pub struct NoteEditor {
+    line_num: LineNumber,
+    text: String,
+    request_focus: bool,
+}
+
+impl NoteEditor {
+    pub fn new(line_num: LineNumber, bookmarks: &Bookmarks) -> Self {
+        Self {
+            line_num,
+            text: bookmarks.note(line_num).unwrap_or_default(),
+            request_focus: true,
+        }
+    }
+
+    /// Shows the editor. Returns false once it should be closed.
+    pub fn show(&mut self, ctx: &egui::Context, id: Id, bookmarks: &Bookmarks) -> bool {
+        let mut open = true;
+        let mut close = false;
+
+        egui::Window::new(format!("Note on Line {}", self.line_num + 1))
+            .id(id)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_TOP, (0.0, 60.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut self.text)
+                        .hint_text("What happened here?")
+                        .desired_rows(6)
+                        .desired_width(400.0),
+                );
+                if self.request_focus {
+                    response.request_focus();
+                    self.request_focus = false;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        bookmarks.set_note(self.line_num, &self.text);
+                        close = true;
+                    }
+                    if bookmarks.has_note(self.line_num) && ui.button("Delete").clicked() {
+                        bookmarks.set_note(self.line_num, "");
+                        close = true;
+                    }
+                    ui.label(RichText::new("Ctrl+Enter to save").weak());
+                });
+
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter)) {
+                    bookmarks.set_note(self.line_num, &self.text);
+                    close = true;
+                }
+            });
+
+        open && !close
+    }
+}
+
+/// An annotated line, as it appears in the incident timeline.
+struct TimelineEntry {
+    line_num: LineNumber,
+    note: String,
+    timestamp: Option<String>,
+    time: Option<Timestamp>,
+    level: Option<String>,
+    message: Option<String>,
+    /// Pretty-printed JSON of the entry, or the raw line if it isn't a log entry.
+    content: String,
+    is_json: bool,
+}
+
+/// Escapes text for a cell of a Markdown table.
+fn table_cell(text: &str) -> String {
+    text.trim()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn string_field(object: &JsonValue, key: &str) -> Option<String> {
+    let value = &object[key];
+    (!value.is_null()).then(|| value.to_string())
+}
+
+/// Builds a Markdown timeline of the annotated lines: a summary table followed by
+/// each entry's note and full JSON. Entries are ordered by time if they all have one.
+pub fn incident_timeline(log_reader: &mut LogFileReader, bookmarks: &Bookmarks) -> String {
+    let line_count = log_reader.line_count();
+    let mut entries: Vec<TimelineEntry> = bookmarks
+        .notes()
+        .into_iter()
+        .filter(|(line_num, _)| *line_num < line_count)
+        .map(|(line_num, note)| {
+            let line = log_reader.read_line(line_num).unwrap_or_default();
+            match LogFileReader::parse_logline(&line) {
+                Some(log_entry) => TimelineEntry {
+                    line_num,
+                    note,
+                    timestamp: Some(log_entry.timestamp.clone()),
+                    time: log_entry.time(),
+                    level: string_field(&log_entry.object, "level"),
+                    message: string_field(&log_entry.object, "message"),
+                    content: json::stringify_pretty(log_entry.object, 2),
+                    is_json: true,
+                },
+                None => TimelineEntry {
+                    line_num,
+                    note,
+                    timestamp: None,
+                    time: None,
+                    level: None,
+                    message: None,
+                    content: line.trim().to_string(),
+                    is_json: false,
+                },
+            }
+        })
+        .collect();
+
+    if entries.iter().all(|entry| entry.time.is_some()) {
+        entries.sort_by_key(|entry| (entry.time, entry.line_num));
+    }
+
+    let file_name = log_reader
+        .line_index()
+        .path()
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut markdown = String::from("# Incident Timeline\n\n");
+    markdown += &format!(
+        "{} annotated entries from `{}`.\n\n",
+        entries.len(),
+        file_name
+    );
+
+    markdown += "| Time | Level | Message | Note |\n| --- | --- | --- | --- |\n";
+    for entry in &entries {
+        let first_note_line = entry.note.lines().next().unwrap_or_default();
+        markdown += &format!(
+            "| {} | {} | {} | {} |\n",
+            table_cell(entry.timestamp.as_deref().unwrap_or("-")),
+            table_cell(entry.level.as_deref().unwrap_or("-")),
+            table_cell(entry.message.as_deref().unwrap_or(&entry.content)),
+            table_cell(first_note_line),
+        );
+    }
+
+    for entry in &entries {
+        let title = match (&entry.timestamp, &entry.level) {
+            (Some(timestamp), Some(level)) => format!("{} {}", timestamp, level),
+            (Some(timestamp), None) => timestamp.clone(),
+            _ => format!("Line {}", entry.line_num + 1),
+        };
+        markdown += &format!("\n## {}\n\n", title);
+
+        for note_line in entry.note.lines() {
+            markdown += &format!("> {}\n", note_line);
+        }
+        markdown += "\n";
+
+        if let Some(ref message) = entry.message {
+            markdown += &format!("**Message:** {}  \n", message.trim());
+        }
+        markdown += &format!("**Line:** {}\n\n", entry.line_num + 1);
+
+        let language = if entry.is_json { "json" } else { "text" };
+        markdown += &format!("```{}\n{}\n```\n", language, entry.content);
+    }
+
+    markdown
+}
+
+/// Asks where to save the incident timeline of the annotated lines and writes it.
+pub fn export_incident_timeline(log_reader: &mut LogFileReader, viewer_state: &mut LogViewerState) {
+    if viewer_state.bookmarks.notes().is_empty() {
+        viewer_state.add_toast(ToastKind::Info, "No notes to export.".into(), 2.0);
+        return;
+    }
+
+    let Some(path) = FileDialog::new()
+        .add_filter("Markdown", &["md"])
+        .set_file_name("incident-timeline.md")
+        .save_file()
+    else {
+        return;
+    };
+
+    let markdown = incident_timeline(log_reader, &viewer_state.bookmarks);
+    match fs::write(&path, markdown) {
+        Ok(()) => viewer_state.add_toast(
+            ToastKind::Success,
+            format!("Exported timeline to {}", path.display()).into(),
+            4.0,
+        ),
+        Err(e) => viewer_state.add_toast(
+            ToastKind::Error,
+            format!("Failed to export timeline: {}", e).into(),
+            10.0,
+        ),
+    }
+}
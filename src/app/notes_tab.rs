@@ -0,0 +1,156 @@
+use egui::{Align, RichText, Ui};
+use egui_extras::{Column, TableBuilder};
+use egui_toast::ToastKind;
+
+use super::{
+    log_entries_table::add_tool_button,
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+    notes::{export_incident_timeline, incident_timeline},
+};
+
+/// Lists the notes attached to lines of the file, and exports them as an incident timeline.
+pub struct NotesTab {
+    scrolled_to_line: Option<LineNumber>,
+}
+
+impl NotesTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            scrolled_to_line: None,
+        })
+    }
+
+    fn toolbar_ui(ui: &mut Ui, log_reader: &mut LogFileReader, viewer_state: &mut LogViewerState) {
+        ui.horizontal(|ui| {
+            let note_count = viewer_state.bookmarks.notes().len();
+            ui.label(format!("{} notes", note_count));
+
+            ui.separator();
+
+            if add_tool_button(ui, "📝", "Edit Note on Selected Line").clicked() {
+                viewer_state.edit_note();
+            }
+
+            ui.separator();
+
+            ui.add_enabled_ui(note_count > 0, |ui| {
+                if ui
+                    .button("Export Timeline…")
+                    .on_hover_text("Save the annotated entries as a Markdown incident timeline")
+                    .clicked()
+                {
+                    export_incident_timeline(log_reader, viewer_state);
+                }
+                if ui.button("Copy as Markdown").clicked() {
+                    let markdown = incident_timeline(log_reader, &viewer_state.bookmarks);
+                    ui.ctx().copy_text(markdown);
+                    viewer_state.add_toast(ToastKind::Info, "Copied timeline.".into(), 2.0);
+                }
+            });
+        });
+        ui.separator();
+    }
+}
+
+impl LogViewTabTrait for NotesTab {
+    fn title(&self) -> egui::WidgetText {
+        "📝 Notes".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        Self::toolbar_ui(ui, log_reader, viewer_state);
+
+        let notes = viewer_state.bookmarks.notes();
+        let mut clicked_line = None;
+        let mut removed_line = None;
+
+        let mut table_builder = TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(40.0))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::initial(300.0).resizable(true).clip(true))
+            .column(Column::remainder().clip(true));
+
+        if self.scrolled_to_line != viewer_state.selected_line_num {
+            self.scrolled_to_line = viewer_state.selected_line_num;
+            if let Some(selected_line) = viewer_state.selected_line_num {
+                if let Ok(row) = notes.binary_search_by_key(&selected_line, |(l, _)| *l) {
+                    table_builder = table_builder.scroll_to_row(row, Some(Align::Center));
+                }
+            }
+        }
+
+        table_builder
+            .header(20.0, |mut header| {
+                for title in ["", "Line", "Time", "Message", "Note"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, notes.len(), |mut row| {
+                    let (line_num, ref note) = notes[row.index()];
+                    row.set_selected(viewer_state.selected_line_num == Some(line_num));
+
+                    row.col(|ui| {
+                        if add_tool_button(ui, "✏", "Edit Note").clicked() {
+                            viewer_state.note_to_edit = Some(line_num);
+                        }
+                        if add_tool_button(ui, "❌", "Delete Note").clicked() {
+                            removed_line = Some(line_num);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new((line_num + 1).to_string()).monospace());
+                    });
+
+                    let log_entry = if line_num < log_reader.line_count() {
+                        log_reader.read_entry(line_num)
+                    } else {
+                        None
+                    };
+                    row.col(|ui| {
+                        if let Some(ref log_entry) = log_entry {
+                            ui.label(RichText::new(&log_entry.timestamp).monospace());
+                        }
+                    });
+                    row.col(|ui| {
+                        if let Some(ref log_entry) = log_entry {
+                            let message = log_entry.field("message");
+                            if !message.is_null() {
+                                ui.label(RichText::new(message.to_string()).monospace());
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(note.lines().next().unwrap_or_default())
+                            .on_hover_text(note);
+                    });
+
+                    if row.response().clicked() {
+                        clicked_line = Some(line_num);
+                    }
+                });
+            });
+
+        if let Some(line_num) = removed_line {
+            viewer_state.bookmarks.set_note(line_num, "");
+        }
+        if clicked_line.is_some() {
+            viewer_state.selected_line_num = clicked_line;
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use egui::{Align, Color32, RichText, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use super::{
+    log_analysis::LineAnalysis,
+    log_entries_table::add_tool_button,
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+};
+
+pub struct MalformedLine {
+    pub line_num: LineNumber,
+    pub message: String,
+    pub column: Option<usize>,
+}
+
+/// Collects every line of a log file that can't be parsed as a log entry.
+#[derive(Default)]
+pub struct ParseDiagnostics {
+    malformed_lines: Vec<MalformedLine>,
+}
+
+impl ParseDiagnostics {
+    /// Malformed lines, ordered by line number.
+    pub fn malformed_lines(&self) -> &[MalformedLine] {
+        &self.malformed_lines
+    }
+
+    /// Returns the first malformed line after the given line, or the first one overall.
+    pub fn next_malformed_line(&self, after: Option<LineNumber>) -> Option<LineNumber> {
+        let index = match after {
+            Some(line_num) => self
+                .malformed_lines
+                .partition_point(|m| m.line_num <= line_num),
+            None => 0,
+        };
+        self.malformed_lines.get(index).map(|m| m.line_num)
+    }
+
+    /// Returns the last malformed line before the given line, or the last one overall.
+    pub fn previous_malformed_line(&self, before: Option<LineNumber>) -> Option<LineNumber> {
+        let index = match before {
+            Some(line_num) => self
+                .malformed_lines
+                .partition_point(|m| m.line_num < line_num),
+            None => self.malformed_lines.len(),
+        };
+        index
+            .checked_sub(1)
+            .map(|index| self.malformed_lines[index].line_num)
+    }
+}
+
+impl LineAnalysis for ParseDiagnostics {
+    /// Why the line can't be parsed and where, if it can't.
+    type Line = Option<(String, Option<usize>)>;
+
+    fn parse_line(line: &str) -> Self::Line {
+        let error = LogFileReader::try_parse_logline(line).err()?;
+        Some((error.to_string(), error.column()))
+    }
+
+    fn add_line(&mut self, line_num: LineNumber, line: Self::Line) {
+        if let Some((message, column)) = line {
+            self.malformed_lines.push(MalformedLine {
+                line_num,
+                message,
+                column,
+            });
+        }
+    }
+}
+
+pub struct ParseDiagnosticsTab {
+    scrolled_to_line: Option<LineNumber>,
+}
+
+impl ParseDiagnosticsTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            scrolled_to_line: None,
+        })
+    }
+
+    fn toolbar_ui(ui: &mut Ui, viewer_state: &mut LogViewerState) {
+        ui.horizontal(|ui| {
+            let (lines_checked, line_count) = viewer_state.parse_diagnostics.progress();
+            let malformed_count = viewer_state
+                .parse_diagnostics
+                .lock()
+                .malformed_lines()
+                .len();
+
+            ui.label(format!("{} malformed lines", malformed_count));
+            if !viewer_state.parse_diagnostics.is_complete() {
+                ui.spinner();
+                ui.label(format!("Checked {} of {} lines", lines_checked, line_count));
+            }
+
+            ui.separator();
+
+            if add_tool_button(ui, "⏶", "Previous Malformed Line").clicked() {
+                viewer_state.select_previous_malformed_line();
+            }
+            if add_tool_button(ui, "⏷", "Next Malformed Line").clicked() {
+                viewer_state.select_next_malformed_line();
+            }
+        });
+        ui.separator();
+    }
+}
+
+impl LogViewTabTrait for ParseDiagnosticsTab {
+    fn title(&self) -> egui::WidgetText {
+        "⚠ Parse Errors".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        Self::toolbar_ui(ui, viewer_state);
+
+        let diagnostics = viewer_state.parse_diagnostics.lock();
+        let malformed_lines = diagnostics.malformed_lines();
+        let mut clicked_line = None;
+
+        let mut table_builder = TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::initial(300.0).resizable(true).clip(true))
+            .column(Column::remainder().clip(true));
+
+        if self.scrolled_to_line != viewer_state.selected_line_num {
+            self.scrolled_to_line = viewer_state.selected_line_num;
+            if let Some(selected_line) = viewer_state.selected_line_num {
+                if let Ok(row) =
+                    malformed_lines.binary_search_by_key(&selected_line, |m| m.line_num)
+                {
+                    table_builder = table_builder.scroll_to_row(row, Some(Align::Center));
+                }
+            }
+        }
+
+        table_builder
+            .header(20.0, |mut header| {
+                for title in ["Line", "Column", "Error", "Content"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, malformed_lines.len(), |mut row| {
+                    let malformed_line = &malformed_lines[row.index()];
+                    row.set_selected(
+                        viewer_state.selected_line_num == Some(malformed_line.line_num),
+                    );
+
+                    row.col(|ui| {
+                        ui.label(
+                            RichText::new((malformed_line.line_num + 1).to_string()).monospace(),
+                        );
+                    });
+                    row.col(|ui| {
+                        if let Some(column) = malformed_line.column {
+                            ui.label(RichText::new(column.to_string()).monospace());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(
+                            RichText::new(&malformed_line.message)
+                                .color(ui.visuals().warn_fg_color),
+                        );
+                    });
+                    row.col(|ui| {
+                        let content = log_reader
+                            .read_line(malformed_line.line_num)
+                            .unwrap_or_default();
+                        ui.label(
+                            RichText::new(content.trim())
+                                .monospace()
+                                .color(Color32::WHITE),
+                        );
+                    });
+
+                    if row.response().clicked() {
+                        clicked_line = Some(malformed_line.line_num);
+                    }
+                });
+            });
+
+        drop(diagnostics);
+        if clicked_line.is_some() {
+            viewer_state.selected_line_num = clicked_line;
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use egui::{Align, Color32, RichText, Ui};
+use egui_dock::DockState;
+use egui_extras::{Column, TableBuilder};
+
+use super::{
+    log_file_reader::LineNumber,
+    log_view::LogView,
+    patterns::{PatternMiner, WILDCARD},
+};
+
+/// Templates with fewer lines than this in both files are never reported as changed.
+const MIN_CHANGED_LINES: usize = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Change {
+    New,
+    Missing,
+    Changed,
+}
+
+/// A template of one file, reduced to what the comparison needs.
+struct TemplateSummary {
+    tokens: Vec<String>,
+    count: usize,
+    example_line: LineNumber,
+}
+
+/// Templates of a file grouped by number of tokens, and the number of lines with a template.
+#[derive(Default)]
+struct FileTemplates {
+    templates: HashMap<usize, Vec<TemplateSummary>>,
+    line_count: usize,
+}
+
+impl FileTemplates {
+    fn of(miner: &PatternMiner) -> Self {
+        let mut file_templates = Self::default();
+        for pattern in miner.patterns() {
+            file_templates.line_count += pattern.count;
+            file_templates
+                .templates
+                .entry(pattern.tokens().len())
+                .or_default()
+                .push(TemplateSummary {
+                    tokens: pattern.tokens().to_vec(),
+                    count: pattern.count,
+                    example_line: pattern.example_line,
+                });
+        }
+        file_templates
+    }
+
+    /// Templates that may describe the same messages as the given tokens.
+    fn compatible<'a>(&'a self, tokens: &'a [String]) -> impl Iterator<Item = &'a TemplateSummary> {
+        self.templates
+            .get(&tokens.len())
+            .into_iter()
+            .flatten()
+            .filter(|template| are_compatible(&template.tokens, tokens))
+    }
+
+    fn share(&self, count: usize) -> f64 {
+        count as f64 / self.line_count.max(1) as f64
+    }
+}
+
+/// Templates mined separately from two files differ in where they have wildcards,
+/// so a wildcard in either template matches any token of the other.
+fn are_compatible(a: &[String], b: &[String]) -> bool {
+    a.iter()
+        .zip(b)
+        .all(|(a, b)| a == b || a == WILDCARD || b == WILDCARD)
+}
+
+/// A template found in only one of the files, or much more or less often in the target.
+struct Difference {
+    change: Change,
+    template: String,
+    baseline_count: usize,
+    target_count: usize,
+    /// Share of the target's lines divided by share of the baseline's lines.
+    ratio: f64,
+    baseline_example: Option<LineNumber>,
+    target_example: Option<LineNumber>,
+}
+
+impl Difference {
+    fn change_text(&self) -> String {
+        match self.change {
+            Change::New => "New".to_string(),
+            Change::Missing => "Missing".to_string(),
+            Change::Changed if self.ratio >= 1.0 => format!("×{:.1} more", self.ratio),
+            Change::Changed => format!("×{:.1} less", 1.0 / self.ratio),
+        }
+    }
+
+    fn change_color(&self) -> Color32 {
+        match self.change {
+            Change::New => Color32::LIGHT_GREEN,
+            Change::Missing => Color32::LIGHT_RED,
+            Change::Changed => Color32::GOLD,
+        }
+    }
+}
+
+fn compare(baseline: &FileTemplates, target: &FileTemplates, threshold: f64) -> Vec<Difference> {
+    let mut differences = vec![];
+
+    for template in target.templates.values().flatten() {
+        let matches: Vec<&TemplateSummary> = baseline.compatible(&template.tokens).collect();
+        let baseline_count: usize = matches.iter().map(|m| m.count).sum();
+        let baseline_example = matches
+            .iter()
+            .max_by_key(|m| m.count)
+            .map(|m| m.example_line);
+        let ratio =
+            target.share(template.count) / baseline.share(baseline_count).max(f64::MIN_POSITIVE);
+
+        let change = if matches.is_empty() {
+            Change::New
+        } else if template.count.max(baseline_count) >= MIN_CHANGED_LINES
+            && (ratio >= threshold || ratio <= 1.0 / threshold)
+        {
+            Change::Changed
+        } else {
+            continue;
+        };
+        differences.push(Difference {
+            change,
+            template: template.tokens.join(" "),
+            baseline_count,
+            target_count: template.count,
+            ratio,
+            baseline_example,
+            target_example: Some(template.example_line),
+        });
+    }
+
+    for template in baseline.templates.values().flatten() {
+        if target.compatible(&template.tokens).next().is_none() {
+            differences.push(Difference {
+                change: Change::Missing,
+                template: template.tokens.join(" "),
+                baseline_count: template.count,
+                target_count: 0,
+                ratio: 0.0,
+                baseline_example: Some(template.example_line),
+                target_example: None,
+            });
+        }
+    }
+
+    differences.sort_by(|a, b| {
+        let order = |d: &Difference| match d.change {
+            Change::New => 0,
+            Change::Missing => 1,
+            Change::Changed => 2,
+        };
+        let magnitude = |d: &Difference| match d.change {
+            Change::New => d.target_count as f64,
+            Change::Missing => d.baseline_count as f64,
+            Change::Changed => d.ratio.ln().abs(),
+        };
+        order(a)
+            .cmp(&order(b))
+            .then(magnitude(b).total_cmp(&magnitude(a)))
+    });
+    differences
+}
+
+/// Identifies the state of both analyses a comparison was computed from.
+#[derive(PartialEq)]
+struct ComparisonInputs {
+    baseline: (PathBuf, usize, usize),
+    target: (PathBuf, usize, usize),
+    threshold: f64,
+}
+
+/// A window comparing the message templates of two open files, e.g. a log from a
+/// broken deploy against a known-good one.
+pub struct PatternComparisonWindow {
+    baseline_path: Option<PathBuf>,
+    target_path: Option<PathBuf>,
+    threshold: f64,
+    show_new: bool,
+    show_missing: bool,
+    show_changed: bool,
+    differences: Vec<Difference>,
+    compared_inputs: Option<ComparisonInputs>,
+}
+
+impl PatternComparisonWindow {
+    pub fn new() -> Self {
+        Self {
+            baseline_path: None,
+            target_path: None,
+            threshold: 2.0,
+            show_new: true,
+            show_missing: true,
+            show_changed: true,
+            differences: vec![],
+            compared_inputs: None,
+        }
+    }
+
+    fn find_view<'a>(tree: &'a mut DockState<LogView>, path: &Path) -> Option<&'a mut LogView> {
+        tree.iter_all_tabs_mut()
+            .map(|(_, log_view)| log_view)
+            .find(|log_view| log_view.file_path() == path)
+    }
+
+    /// Mines the patterns of the given file. Returns the state of the analysis, and
+    /// whether it's complete.
+    fn analysis_state(
+        tree: &mut DockState<LogView>,
+        path: &Path,
+    ) -> Option<((PathBuf, usize, usize), bool)> {
+        let patterns = Self::find_view(tree, path)?.patterns();
+        let lines_analyzed = patterns.lock().lines_analyzed();
+        let state = (path.to_owned(), patterns.generation(), lines_analyzed);
+        Some((state, patterns.is_complete()))
+    }
+
+    fn file_templates(tree: &mut DockState<LogView>, path: &Path) -> Option<FileTemplates> {
+        let patterns = Self::find_view(tree, path)?.patterns();
+        let file_templates = FileTemplates::of(&patterns.lock());
+        Some(file_templates)
+    }
+
+    /// Recomputes the comparison once both files have been analyzed, and again
+    /// whenever either is reloaded.
+    fn update(&mut self, ctx: &egui::Context, tree: &mut DockState<LogView>) {
+        let (Some(baseline_path), Some(target_path)) = (&self.baseline_path, &self.target_path)
+        else {
+            self.compared_inputs = None;
+            return;
+        };
+        let (Some((baseline_state, baseline_complete)), Some((target_state, target_complete))) = (
+            Self::analysis_state(tree, baseline_path),
+            Self::analysis_state(tree, target_path),
+        ) else {
+            return;
+        };
+        if !baseline_complete || !target_complete {
+            ctx.request_repaint_after(Duration::from_millis(250));
+            return;
+        }
+
+        let inputs = ComparisonInputs {
+            baseline: baseline_state,
+            target: target_state,
+            threshold: self.threshold,
+        };
+        if self.compared_inputs.as_ref() == Some(&inputs) {
+            return;
+        }
+        let (Some(baseline), Some(target)) = (
+            Self::file_templates(tree, baseline_path),
+            Self::file_templates(tree, target_path),
+        ) else {
+            return;
+        };
+        self.differences = compare(&baseline, &target, self.threshold);
+        self.compared_inputs = Some(inputs);
+    }
+
+    /// Shows the window. Returns false once it has been closed, along with the
+    /// file and line of the example clicked, if any.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        tree: &mut DockState<LogView>,
+    ) -> (bool, Option<(PathBuf, LineNumber)>) {
+        let open_files: Vec<PathBuf> = tree
+            .iter_all_tabs()
+            .map(|(_, log_view)| log_view.file_path().to_owned())
+            .collect();
+        for path in [&mut self.baseline_path, &mut self.target_path] {
+            if path.as_ref().is_some_and(|p| !open_files.contains(p)) {
+                *path = None;
+            }
+        }
+        if self.baseline_path.is_none() {
+            self.baseline_path = open_files.first().cloned();
+        }
+        if self.target_path.is_none() {
+            self.target_path = open_files.get(1).cloned();
+        }
+
+        self.update(ctx, tree);
+
+        let mut open = true;
+        let mut clicked_example = None;
+        egui::Window::new("Compare Patterns")
+            .open(&mut open)
+            .default_size((720.0, 420.0))
+            .show(ctx, |ui| {
+                self.ui_toolbar(ui, tree, &open_files);
+                ui.separator();
+                if self.compared_inputs.is_some() {
+                    clicked_example = self.ui_differences(ui);
+                }
+            });
+
+        (open, clicked_example)
+    }
+
+    fn file_combo_box(
+        ui: &mut Ui,
+        id: &str,
+        selected: &mut Option<PathBuf>,
+        open_files: &[PathBuf],
+    ) {
+        let file_name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        egui::ComboBox::from_id_source(id)
+            .selected_text(selected.as_deref().map(file_name).unwrap_or_default())
+            .show_ui(ui, |ui| {
+                for path in open_files {
+                    ui.selectable_value(selected, Some(path.clone()), file_name(path))
+                        .on_hover_text(path.to_string_lossy());
+                }
+            });
+    }
+
+    fn ui_toolbar(&mut self, ui: &mut Ui, tree: &mut DockState<LogView>, open_files: &[PathBuf]) {
+        ui.horizontal(|ui| {
+            ui.label("Baseline:");
+            Self::file_combo_box(ui, "compare_baseline", &mut self.baseline_path, open_files);
+            ui.label("Compare with:");
+            Self::file_combo_box(ui, "compare_target", &mut self.target_path, open_files);
+            if ui
+                .button("⇄")
+                .on_hover_text("Swap Baseline and Compared File")
+                .clicked()
+            {
+                std::mem::swap(&mut self.baseline_path, &mut self.target_path);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_new, "New");
+            ui.checkbox(&mut self.show_missing, "Missing");
+            ui.checkbox(&mut self.show_changed, "Changed by at least");
+            ui.add(
+                egui::DragValue::new(&mut self.threshold)
+                    .clamp_range(1.1..=100.0)
+                    .speed(0.1)
+                    .prefix("×"),
+            );
+
+            ui.separator();
+
+            if open_files.len() < 2 {
+                ui.label("Open another file to compare with.");
+                return;
+            }
+            for path in [&self.baseline_path, &self.target_path]
+                .into_iter()
+                .flatten()
+            {
+                if let Some(log_view) = Self::find_view(tree, path) {
+                    let patterns = log_view.patterns();
+                    if !patterns.is_complete() {
+                        let (lines_analyzed, line_count) = patterns.progress();
+                        ui.spinner();
+                        ui.label(format!(
+                            "Mining patterns of {}… {} of {} lines",
+                            path.file_name().unwrap_or_default().to_string_lossy(),
+                            lines_analyzed,
+                            line_count
+                        ));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Shows the differences. Returns the file and line of the example clicked, if any.
+    fn ui_differences(&self, ui: &mut Ui) -> Option<(PathBuf, LineNumber)> {
+        let differences: Vec<&Difference> = self
+            .differences
+            .iter()
+            .filter(|d| match d.change {
+                Change::New => self.show_new,
+                Change::Missing => self.show_missing,
+                Change::Changed => self.show_changed,
+            })
+            .collect();
+        if differences.is_empty() {
+            ui.label("No differences found.");
+            return None;
+        }
+
+        let mut clicked_example = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(80.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(110.0))
+            .column(Column::remainder().clip(true))
+            .header(20.0, |mut header| {
+                for title in ["Change", "Baseline", "Compared", "Examples", "Template"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, differences.len(), |mut row| {
+                    let difference = differences[row.index()];
+                    row.col(|ui| {
+                        ui.label(
+                            RichText::new(difference.change_text())
+                                .color(difference.change_color()),
+                        );
+                    });
+                    for count in [difference.baseline_count, difference.target_count] {
+                        row.col(|ui| {
+                            ui.label(RichText::new(count.to_string()).monospace());
+                        });
+                    }
+                    row.col(|ui| {
+                        let examples = [
+                            ("Baseline", &self.baseline_path, difference.baseline_example),
+                            ("Compared", &self.target_path, difference.target_example),
+                        ];
+                        for (label, path, example) in examples {
+                            if let (Some(path), Some(line_num)) = (path, example) {
+                                if ui
+                                    .link(label)
+                                    .on_hover_text(format!("Go to line {}", line_num + 1))
+                                    .clicked()
+                                {
+                                    clicked_example = Some((path.clone(), line_num));
+                                }
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(&difference.template).monospace());
+                    });
+                });
+            });
+
+        clicked_example
+    }
+}
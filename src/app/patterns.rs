@@ -0,0 +1,203 @@
+//! Clusters log messages into templates with a simplified Drain algorithm
+//! (He et al., "Drain: An Online Log Parsing Approach with Fixed Depth Tree").
+//!
+//! Messages are split into tokens, and tokens that look variable (numbers, IDs,
+//! IP addresses) are masked. Messages with the same number of tokens and the same
+//! first token are compared with the templates of that group; a message joins the
+//! most similar template if enough tokens match, and the template's differing
+//! tokens become wildcards. Otherwise the message starts a new template.
+
+use std::collections::HashMap;
+
+use super::{
+    level_filter::LogLevel,
+    log_analysis::LineAnalysis,
+    log_file_reader::{LineNumber, LogFileReader},
+    timestamp::Timestamp,
+};
+
+pub const WILDCARD: &str = "<*>";
+
+/// Fraction of tokens that must match for a message to join a template.
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Marks lines without a message in `PatternMiner::line_patterns`.
+const NO_PATTERN: u32 = u32::MAX;
+
+/// A message template and statistics of the lines matching it.
+pub struct Pattern {
+    tokens: Vec<String>,
+    pub count: usize,
+    pub first_time: Option<Timestamp>,
+    pub last_time: Option<Timestamp>,
+    /// Line counts indexed by `LogLevel::index`.
+    pub level_counts: [usize; LogLevel::ALL.len()],
+    /// First line matching the template.
+    pub example_line: LineNumber,
+}
+
+impl Pattern {
+    pub fn template(&self) -> String {
+        self.tokens.join(" ")
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Counts the tokens of `tokens` equal to the template's non-wildcard tokens.
+    fn matching_tokens(&self, tokens: &[String]) -> usize {
+        self.tokens
+            .iter()
+            .zip(tokens)
+            .filter(|(template_token, token)| {
+                *template_token != WILDCARD && template_token == token
+            })
+            .count()
+    }
+
+    fn add(&mut self, tokens: Vec<String>, time: Option<Timestamp>, level: LogLevel) {
+        for (template_token, token) in self.tokens.iter_mut().zip(tokens) {
+            if *template_token != token {
+                *template_token = WILDCARD.to_string();
+            }
+        }
+        self.count += 1;
+        self.level_counts[level.index()] += 1;
+        if let Some(time) = time {
+            self.first_time = Some(self.first_time.map_or(time, |t| t.min(time)));
+            self.last_time = Some(self.last_time.map_or(time, |t| t.max(time)));
+        }
+    }
+}
+
+/// Replaces tokens that are likely to vary between messages of the same kind.
+fn mask_token(token: &str) -> String {
+    let trimmed =
+        token.trim_matches(|c: char| matches!(c, ',' | ';' | '(' | ')' | '[' | ']' | '"' | '\''));
+    if is_ip_address(trimmed) {
+        "<IP>".to_string()
+    } else if is_uuid(trimmed) {
+        "<UUID>".to_string()
+    } else if token.chars().any(|c| c.is_ascii_digit()) {
+        WILDCARD.to_string()
+    } else {
+        token.to_string()
+    }
+}
+
+/// Matches IPv4 addresses with an optional port, e.g. `10.0.0.1:8080`.
+fn is_ip_address(token: &str) -> bool {
+    let address = token.split_once(':').map_or(token, |(address, _)| address);
+    let octets: Vec<&str> = address.split('.').collect();
+    octets.len() == 4
+        && octets.iter().all(|octet| {
+            !octet.is_empty() && octet.len() <= 3 && octet.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+fn is_uuid(token: &str) -> bool {
+    let groups: Vec<&str> = token.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// The templates of a log file's messages, and the template of every line.
+#[derive(Default)]
+pub struct PatternMiner {
+    patterns: Vec<Pattern>,
+    /// Templates grouped by token count and first token.
+    groups: HashMap<(usize, String), Vec<usize>>,
+    /// Index of the template of every line analyzed, or `NO_PATTERN`.
+    line_patterns: Vec<u32>,
+}
+
+impl PatternMiner {
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    pub fn lines_analyzed(&self) -> usize {
+        self.line_patterns.len()
+    }
+
+    /// Appends the lines from `start` on that match the given template to `lines`.
+    pub fn member_lines(&self, pattern: usize, start: LineNumber, lines: &mut Vec<LineNumber>) {
+        let Some(line_patterns) = self.line_patterns.get(start..) else {
+            return;
+        };
+        lines.extend(
+            line_patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| **p as usize == pattern)
+                .map(|(offset, _)| start + offset),
+        );
+    }
+
+    fn add_message(
+        &mut self,
+        line_num: LineNumber,
+        message: &str,
+        time: Option<Timestamp>,
+        level: LogLevel,
+    ) -> usize {
+        let tokens: Vec<String> = message.split_whitespace().map(mask_token).collect();
+        let group_key = (tokens.len(), tokens.first().cloned().unwrap_or_default());
+        let group = self.groups.entry(group_key).or_default();
+
+        let best_match = group
+            .iter()
+            .map(|&index| (index, self.patterns[index].matching_tokens(&tokens)))
+            .max_by_key(|(_, matching)| *matching)
+            .filter(|(_, matching)| {
+                tokens.is_empty() || *matching as f64 / tokens.len() as f64 >= SIMILARITY_THRESHOLD
+            });
+
+        match best_match {
+            Some((index, _)) => {
+                self.patterns[index].add(tokens, time, level);
+                index
+            }
+            None => {
+                let index = self.patterns.len();
+                group.push(index);
+                let mut pattern = Pattern {
+                    tokens: tokens.clone(),
+                    count: 0,
+                    first_time: None,
+                    last_time: None,
+                    level_counts: Default::default(),
+                    example_line: line_num,
+                };
+                pattern.add(tokens, time, level);
+                self.patterns.push(pattern);
+                index
+            }
+        }
+    }
+}
+
+impl LineAnalysis for PatternMiner {
+    /// Message, time and level of an entry with a message.
+    type Line = Option<(String, Option<Timestamp>, LogLevel)>;
+
+    fn parse_line(line: &str) -> Self::Line {
+        let log_entry = LogFileReader::parse_logline(line)?;
+        let message = log_entry.object["message"].as_str()?.to_string();
+        let level = log_entry.object["level"]
+            .as_str()
+            .map_or(LogLevel::Other, LogLevel::from_name);
+        Some((message, log_entry.time(), level))
+    }
+
+    fn add_line(&mut self, line_num: LineNumber, line: Self::Line) {
+        let pattern =
+            line.map(|(message, time, level)| self.add_message(line_num, &message, time, level));
+        self.line_patterns
+            .push(pattern.map_or(NO_PATTERN, |index| index as u32));
+    }
+}
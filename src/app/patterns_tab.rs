@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use egui::{Align, Color32, RichText, Sense, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use super::{
+    level_filter::LogLevel,
+    log_entries_table::{
+        add_tool_button, color_from_loglevel, next_lines_generation, LogEntriesTable, TableLines,
+    },
+    log_file_reader::{LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+    patterns::Pattern,
+    timestamp::format_timestamp,
+};
+
+const LEVEL_BAR_WIDTH: f32 = 80.0;
+
+/// Lists the message templates of the file, and the lines of the selected one.
+pub struct PatternsTab {
+    filter_text: String,
+    selected_pattern: Option<usize>,
+    /// Lines of the selected pattern.
+    members: Vec<LineNumber>,
+    /// Replaced whenever `members` is cleared.
+    members_generation: usize,
+    /// Number of analyzed lines searched for members so far.
+    members_scanned: usize,
+    generation: usize,
+    log_entries_table: LogEntriesTable,
+}
+
+impl PatternsTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            filter_text: String::new(),
+            selected_pattern: None,
+            members: vec![],
+            members_generation: next_lines_generation(),
+            members_scanned: 0,
+            generation: 0,
+            log_entries_table: LogEntriesTable::new(),
+        })
+    }
+
+    fn select_pattern(&mut self, pattern: Option<usize>) {
+        self.selected_pattern = pattern;
+        self.members.clear();
+        self.members_generation = next_lines_generation();
+        self.members_scanned = 0;
+    }
+
+    fn toolbar_ui(&mut self, ui: &mut Ui, viewer_state: &LogViewerState) {
+        ui.horizontal(|ui| {
+            let pattern_count = viewer_state.patterns.lock().patterns().len();
+            ui.label(format!("{} patterns", pattern_count));
+            if !viewer_state.patterns.is_complete() {
+                let (lines_analyzed, line_count) = viewer_state.patterns.progress();
+                ui.spinner();
+                ui.label(format!(
+                    "Analyzed {} of {} lines",
+                    lines_analyzed, line_count
+                ));
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter_text)
+                    .hint_text("Filter patterns")
+                    .desired_width(200.0),
+            );
+            if !self.filter_text.is_empty() && add_tool_button(ui, "✖", "Clear Filter").clicked()
+            {
+                self.filter_text.clear();
+            }
+        });
+        ui.separator();
+    }
+
+    /// Paints the share of each level among the pattern's lines as a stacked bar.
+    fn ui_level_bar(ui: &mut Ui, pattern: &Pattern) {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(LEVEL_BAR_WIDTH, 10.0), Sense::hover());
+        let mut x = rect.left();
+        for level in LogLevel::ALL {
+            let count = pattern.level_counts[level.index()];
+            if count == 0 {
+                continue;
+            }
+            let width = rect.width() * count as f32 / pattern.count as f32;
+            let segment = egui::Rect::from_min_max(
+                egui::pos2(x, rect.top()),
+                egui::pos2(x + width, rect.bottom()),
+            );
+            ui.painter()
+                .rect_filled(segment, 0.0, color_from_loglevel(level.name()));
+            x += width;
+        }
+
+        response.on_hover_ui(|ui| {
+            for level in LogLevel::ALL {
+                let count = pattern.level_counts[level.index()];
+                if count > 0 {
+                    ui.label(
+                        RichText::new(format!("{}: {}", level.name(), count))
+                            .color(color_from_loglevel(level.name())),
+                    );
+                }
+            }
+        });
+    }
+
+    fn ui_patterns(&mut self, ui: &mut Ui, viewer_state: &LogViewerState) {
+        let miner = viewer_state.patterns.lock();
+        let patterns = miner.patterns();
+
+        let filter_text = self.filter_text.to_lowercase();
+        let mut rows: Vec<(usize, String)> = patterns
+            .iter()
+            .enumerate()
+            .map(|(index, pattern)| (index, pattern.template()))
+            .filter(|(_, template)| {
+                filter_text.is_empty() || template.to_lowercase().contains(&filter_text)
+            })
+            .collect();
+        rows.sort_by_key(|(index, _)| (std::cmp::Reverse(patterns[*index].count), *index));
+
+        let mut clicked_pattern = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .min_scrolled_height(0.0)
+            .max_scroll_height(f32::INFINITY)
+            .auto_shrink(false)
+            .sense(Sense::click())
+            .cell_layout(egui::Layout::left_to_right(Align::Center))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(LEVEL_BAR_WIDTH))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::remainder().clip(true))
+            .header(20.0, |mut header| {
+                for title in ["Count", "Levels", "First Seen", "Last Seen", "Template"] {
+                    header.col(|ui| {
+                        ui.label(RichText::new(title).strong());
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(16.0, rows.len(), |mut row| {
+                    let (index, ref template) = rows[row.index()];
+                    let pattern = &patterns[index];
+                    row.set_selected(self.selected_pattern == Some(index));
+
+                    row.col(|ui| {
+                        ui.label(RichText::new(pattern.count.to_string()).monospace());
+                    });
+                    row.col(|ui| {
+                        Self::ui_level_bar(ui, pattern);
+                    });
+                    for time in [pattern.first_time, pattern.last_time] {
+                        row.col(|ui| {
+                            if let Some(time) = time {
+                                ui.label(RichText::new(format_timestamp(time)).monospace());
+                            }
+                        });
+                    }
+                    row.col(|ui| {
+                        ui.label(RichText::new(template).monospace().color(Color32::WHITE));
+                    });
+
+                    if row.response().clicked() {
+                        clicked_pattern = Some(index);
+                    }
+                });
+            });
+
+        drop(miner);
+        if let Some(index) = clicked_pattern {
+            let pattern = (self.selected_pattern != Some(index)).then_some(index);
+            self.select_pattern(pattern);
+        }
+    }
+
+    /// Collects the lines of the selected pattern analyzed since the last frame.
+    fn update_members(&mut self, viewer_state: &LogViewerState) {
+        if viewer_state.patterns.generation() != self.generation {
+            self.generation = viewer_state.patterns.generation();
+            self.select_pattern(None);
+        }
+        let Some(pattern) = self.selected_pattern else {
+            return;
+        };
+        let miner = viewer_state.patterns.lock();
+        miner.member_lines(pattern, self.members_scanned, &mut self.members);
+        self.members_scanned = miner.lines_analyzed();
+    }
+}
+
+impl LogViewTabTrait for PatternsTab {
+    fn title(&self) -> egui::WidgetText {
+        "🧩 Patterns".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        viewer_state.patterns.sync(log_reader);
+        if !viewer_state.patterns.is_complete() {
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+        self.update_members(viewer_state);
+
+        self.toolbar_ui(ui, viewer_state);
+
+        if self.selected_pattern.is_none() {
+            self.ui_patterns(ui, viewer_state);
+            return;
+        }
+
+        egui::TopBottomPanel::top(ui.id().with("patterns"))
+            .resizable(true)
+            .default_height(200.0)
+            .show_inside(ui, |ui| {
+                self.ui_patterns(ui, viewer_state);
+            });
+
+        let mut clear_selection = false;
+        let member_count = self.members.len();
+        self.log_entries_table.ui(
+            ui,
+            log_reader,
+            viewer_state,
+            Some(TableLines::Lines(&self.members, self.members_generation)),
+            None,
+            None,
+            |ui| {
+                ui.label(format!("{} lines of the selected pattern", member_count));
+                if add_tool_button(ui, "✖", "Show All Patterns").clicked() {
+                    clear_selection = true;
+                }
+            },
+        );
+        if clear_selection {
+            self.select_pattern(None);
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! A small query language for filtering log entries by field.
+//!
+//! ```text
+//! level >= WARNING and tag = "Main" and not message ~ /heartbeat/i
+//! duration_ms > 500 or (exists error.stack and http.status != 200)
+//! ```
+//!
+//! * Fields are top-level keys, dotted paths or JSON Pointers (see `FieldPath`).
+//! * Comparisons: `=` (or `==`), `!=`, `<`, `<=`, `>`, `>=`.
+//!   Numbers are compared numerically, severity levels by severity, anything else as text.
+//!   Comparisons only match entries that have the field, `!=` included.
+//! * Regex matches: `field ~ /regex/` and `field !~ /regex/`, with an optional `i` flag.
+//!   Like comparisons, `!~` only matches entries that have the field.
+//! * Existence: `exists field`.
+//! * Combinators: `and`, `or`, `not` and parentheses.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use json::JsonValue;
+use regex::{Regex, RegexBuilder};
+
+use super::log_file_reader::{LogEntry, LogFileReader};
+
+#[derive(Debug)]
+pub struct QueryError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+#[derive(Clone, Debug)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Op(CompareOp),
+    Word(String),
+    Text(String),
+    Regex(Regex),
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Exists(String),
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: String,
+    },
+    Match {
+        field: String,
+        regex: Regex,
+        negate: bool,
+    },
+}
+
+/// A parsed query that can be evaluated against log entries.
+#[derive(Clone, Debug)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end_position: text.len(),
+        };
+        let expr = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(QueryError {
+                message: "Unexpected input".to_string(),
+                position: token.position,
+            });
+        }
+        Ok(Self { expr })
+    }
+
+    pub fn matches(&self, log_entry: &LogEntry) -> bool {
+        self.expr.evaluate(log_entry)
+    }
+
+    /// Parses the line as a log entry and evaluates the query against it.
+    /// Lines that aren't valid log entries never match.
+    pub fn matches_line(&self, line: &str) -> bool {
+        LogFileReader::parse_logline(line).is_some_and(|log_entry| self.matches(&log_entry))
+    }
+}
+
+/// Rank of a well-known severity level, for ordered comparisons.
+pub fn severity_rank(level: &str) -> Option<u8> {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => Some(0),
+        "DEBUG" => Some(1),
+        "INFO" => Some(2),
+        "WARN" | "WARNING" => Some(3),
+        "ERROR" => Some(4),
+        "FATAL" | "CRITICAL" => Some(5),
+        _ => None,
+    }
+}
+
+/// Writes text as a field or value of a query, quoted unless it's a plain word.
+pub fn query_literal(text: &str) -> String {
+    let is_keyword = ["and", "or", "not", "exists"]
+        .iter()
+        .any(|keyword| text.eq_ignore_ascii_case(keyword));
+    if !text.is_empty() && !is_keyword && text.chars().all(is_word_char) {
+        return text.to_string();
+    }
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn value_text(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::Null => None,
+        _ => Some(value.to_string()),
+    }
+}
+
+fn compare(value: &JsonValue, literal: &str) -> Option<Ordering> {
+    let text = value_text(value)?;
+
+    if let (Ok(lhs), Ok(rhs)) = (text.parse::<f64>(), literal.parse::<f64>()) {
+        return lhs.partial_cmp(&rhs);
+    }
+    if let (Some(lhs), Some(rhs)) = (severity_rank(&text), severity_rank(literal)) {
+        return Some(lhs.cmp(&rhs));
+    }
+    Some(text.as_str().cmp(literal))
+}
+
+impl Expr {
+    fn evaluate(&self, log_entry: &LogEntry) -> bool {
+        match self {
+            Expr::And(lhs, rhs) => lhs.evaluate(log_entry) && rhs.evaluate(log_entry),
+            Expr::Or(lhs, rhs) => lhs.evaluate(log_entry) || rhs.evaluate(log_entry),
+            Expr::Not(expr) => !expr.evaluate(log_entry),
+            Expr::Exists(field) => !log_entry.field(field).is_null(),
+            Expr::Match {
+                field,
+                regex,
+                negate,
+            } => value_text(log_entry.field(field))
+                .is_some_and(|text| regex.is_match(&text) != *negate),
+            Expr::Compare { field, op, value } => {
+                let ordering = compare(log_entry.field(field), value);
+                match op {
+                    CompareOp::Eq => ordering == Some(Ordering::Equal),
+                    CompareOp::Ne => matches!(ordering, Some(Ordering::Less | Ordering::Greater)),
+                    CompareOp::Lt => ordering == Some(Ordering::Less),
+                    CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    CompareOp::Gt => ordering == Some(Ordering::Greater),
+                    CompareOp::Ge => {
+                        matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                    }
+                    CompareOp::Match | CompareOp::NotMatch => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"()=!<>~\"'".contains(c)
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens: Vec<Token> = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let after_match_op = matches!(
+            tokens.last(),
+            Some(Token {
+                kind: TokenKind::Op(CompareOp::Match | CompareOp::NotMatch),
+                ..
+            })
+        );
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                TokenKind::LParen
+            }
+            ')' => {
+                chars.next();
+                TokenKind::RParen
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                chars.next();
+                let next = chars.peek().map(|(_, c)| *c);
+                let (op, consume_next) = match (c, next) {
+                    ('=', Some('=')) => (CompareOp::Eq, true),
+                    ('=', _) => (CompareOp::Eq, false),
+                    ('!', Some('=')) => (CompareOp::Ne, true),
+                    ('!', Some('~')) => (CompareOp::NotMatch, true),
+                    ('<', Some('=')) => (CompareOp::Le, true),
+                    ('<', _) => (CompareOp::Lt, false),
+                    ('>', Some('=')) => (CompareOp::Ge, true),
+                    ('>', _) => (CompareOp::Gt, false),
+                    ('~', _) => (CompareOp::Match, false),
+                    _ => {
+                        return Err(QueryError {
+                            message: "Expected '!=' or '!~'".to_string(),
+                            position,
+                        })
+                    }
+                };
+                if consume_next {
+                    chars.next();
+                }
+                TokenKind::Op(op)
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, escaped)) => value.push(escaped),
+                            None => break,
+                        },
+                        Some((_, quote)) if quote == c => break,
+                        Some((_, other)) => value.push(other),
+                        None => {
+                            return Err(QueryError {
+                                message: "Unterminated string".to_string(),
+                                position,
+                            })
+                        }
+                    }
+                }
+                TokenKind::Text(value)
+            }
+            '/' if after_match_op => {
+                chars.next();
+                let mut pattern = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '/')) => pattern.push('/'),
+                            Some((_, escaped)) => {
+                                pattern.push('\\');
+                                pattern.push(escaped);
+                            }
+                            None => break,
+                        },
+                        Some((_, '/')) => break,
+                        Some((_, other)) => pattern.push(other),
+                        None => {
+                            return Err(QueryError {
+                                message: "Unterminated regex".to_string(),
+                                position,
+                            })
+                        }
+                    }
+                }
+                let case_insensitive = chars.next_if(|(_, c)| *c == 'i').is_some();
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(case_insensitive)
+                    .build()
+                    .map_err(|e| QueryError {
+                        message: format!("Invalid regex: {}", e),
+                        position,
+                    })?;
+                TokenKind::Regex(regex)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                TokenKind::Word(word)
+            }
+        };
+
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    end_position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Result<Token, QueryError> {
+        let token = self.tokens.get(self.next).cloned().ok_or(QueryError {
+            message: "Unexpected end of query".to_string(),
+            position: self.end_position,
+        })?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.next += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.next += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        if self.peek_keyword("not") {
+            self.next += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek_keyword("exists") {
+            self.next += 1;
+            return Ok(Expr::Exists(self.parse_field()?));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, QueryError> {
+        if let Some(Token {
+            kind: TokenKind::LParen,
+            ..
+        }) = self.peek()
+        {
+            self.next += 1;
+            let expr = self.parse_or()?;
+            let token = self.advance()?;
+            return match token.kind {
+                TokenKind::RParen => Ok(expr),
+                _ => Err(QueryError {
+                    message: "Expected ')'".to_string(),
+                    position: token.position,
+                }),
+            };
+        }
+
+        let field = self.parse_field()?;
+        let op_token = self.advance()?;
+        let TokenKind::Op(op) = op_token.kind else {
+            return Err(QueryError {
+                message: format!("Expected a comparison operator after '{}'", field),
+                position: op_token.position,
+            });
+        };
+
+        let value_token = self.advance()?;
+        match (op, value_token.kind) {
+            (CompareOp::Match | CompareOp::NotMatch, TokenKind::Regex(regex)) => Ok(Expr::Match {
+                field,
+                regex,
+                negate: op == CompareOp::NotMatch,
+            }),
+            (
+                CompareOp::Match | CompareOp::NotMatch,
+                TokenKind::Word(pattern) | TokenKind::Text(pattern),
+            ) => Ok(Expr::Match {
+                field,
+                regex: Regex::new(&pattern).map_err(|e| QueryError {
+                    message: format!("Invalid regex: {}", e),
+                    position: value_token.position,
+                })?,
+                negate: op == CompareOp::NotMatch,
+            }),
+            (_, TokenKind::Word(value) | TokenKind::Text(value)) => {
+                Ok(Expr::Compare { field, op, value })
+            }
+            _ => Err(QueryError {
+                message: "Expected a value".to_string(),
+                position: value_token.position,
+            }),
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<String, QueryError> {
+        let token = self.advance()?;
+        match token.kind {
+            TokenKind::Word(field) | TokenKind::Text(field) => Ok(field),
+            _ => Err(QueryError {
+                message: "Expected a field name".to_string(),
+                position: token.position,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(query: &str, line: &str) -> bool {
+        Query::parse(query).unwrap().matches_line(line)
+    }
+
+    const ENTRY: &str = r#"{"t": "2023-06-25T00:49:20Z", "level": "WARNING", "tag": "db",
+        "duration_ms": 750, "message": "slow query", "http": {"status": 503}}"#;
+
+    #[test]
+    fn comparisons() {
+        assert!(matches("tag = db", ENTRY));
+        assert!(matches("tag == \"db\"", ENTRY));
+        assert!(matches("tag != main", ENTRY));
+        assert!(matches("duration_ms > 500", ENTRY));
+        assert!(matches("duration_ms <= 750", ENTRY));
+        assert!(!matches("duration_ms < 80", ENTRY));
+        assert!(matches("http.status >= 500", ENTRY));
+        assert!(matches("/http/status = 503", ENTRY));
+    }
+
+    #[test]
+    fn severity_levels_compare_by_severity() {
+        assert!(matches("level >= WARN", ENTRY));
+        assert!(matches("level > info", ENTRY));
+        assert!(!matches("level >= ERROR", ENTRY));
+    }
+
+    #[test]
+    fn regex_matches() {
+        assert!(matches("message ~ /slow/", ENTRY));
+        assert!(matches("message ~ /SLOW/i", ENTRY));
+        assert!(!matches("message ~ /SLOW/", ENTRY));
+        assert!(matches("message !~ /fast/", ENTRY));
+        assert!(!matches("message !~ /slow/", ENTRY));
+    }
+
+    #[test]
+    fn missing_fields_never_match() {
+        assert!(!matches("missing = x", ENTRY));
+        assert!(!matches("missing != x", ENTRY));
+        assert!(!matches("missing > 1", ENTRY));
+        assert!(!matches("missing ~ /x/", ENTRY));
+        assert!(!matches("missing !~ /x/", ENTRY));
+        assert!(!matches("exists missing", ENTRY));
+        assert!(matches("not exists missing", ENTRY));
+        assert!(matches("exists http.status", ENTRY));
+    }
+
+    #[test]
+    fn combinators() {
+        assert!(matches("tag = db and duration_ms > 500", ENTRY));
+        assert!(matches("tag = main or duration_ms > 500", ENTRY));
+        assert!(!matches("not tag = db", ENTRY));
+        assert!(matches(
+            "(tag = main or level = WARNING) and not message ~ /heartbeat/",
+            ENTRY
+        ));
+    }
+
+    #[test]
+    fn invalid_lines_never_match() {
+        assert!(!matches("not exists missing", "not json"));
+        assert!(!matches(
+            "not exists missing",
+            r#"{"message": "no timestamp"}"#
+        ));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("tag =").is_err());
+        assert!(Query::parse("(tag = db").is_err());
+        assert!(Query::parse("message ~ /(/").is_err());
+        assert!(Query::parse("tag = db extra").is_err());
+    }
+
+    #[test]
+    fn literals_round_trip() {
+        for text in ["db", "two words", "and", "quote\"d", "back\\slash", ""] {
+            let query = format!("tag = {}", query_literal(text));
+            let line = json::stringify(json::object! { t: "2023-06-25T00:49:20Z", tag: text });
+            assert!(matches(&query, &line), "{}", query);
+        }
+    }
+}
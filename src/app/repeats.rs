@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use json::JsonValue;
+
+use super::{
+    level_filter::SourceFingerprint,
+    log_analysis::{BackgroundAnalysis, LineAnalysis},
+    log_entries_table::{next_lines_generation, TableLines},
+    log_file_reader::{LineNumber, LogFileReader},
+};
+
+/// Which entries count as repeats of each other.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollapseMode {
+    Off,
+    SameMessage,
+    SameTagAndMessage,
+}
+
+impl CollapseMode {
+    pub const ALL: [CollapseMode; 3] = [
+        CollapseMode::Off,
+        CollapseMode::SameMessage,
+        CollapseMode::SameTagAndMessage,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollapseMode::Off => "Show Repeats",
+            CollapseMode::SameMessage => "Collapse Same Message",
+            CollapseMode::SameTagAndMessage => "Collapse Same Tag & Message",
+        }
+    }
+}
+
+/// Ids of the message, and of the tag and message, of every line. Equal texts get
+/// equal ids. Lines without a message, including lines that aren't log entries,
+/// have no ids and are never repeats.
+#[derive(Default)]
+pub struct MessageIndex {
+    keys: Vec<Option<(u32, u32)>>,
+    message_ids: HashMap<String, u32>,
+    tag_and_message_ids: HashMap<(Option<String>, u32), u32>,
+}
+
+impl MessageIndex {
+    /// Key of the given line for the given mode, if the line has been analyzed.
+    fn key(&self, line_num: LineNumber, mode: CollapseMode) -> Option<Option<u32>> {
+        let keys = self.keys.get(line_num)?;
+        Some(keys.map(|(message, tag_and_message)| match mode {
+            CollapseMode::SameTagAndMessage => tag_and_message,
+            _ => message,
+        }))
+    }
+}
+
+/// Text of a field, or None if the entry doesn't have it.
+fn text_of(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::Null => None,
+        _ => Some(value.as_str().map_or_else(|| value.dump(), str::to_string)),
+    }
+}
+
+/// Id of `key`, assigning the next free one if it hasn't been seen.
+fn id_of<K: Hash + Eq>(ids: &mut HashMap<K, u32>, key: K) -> u32 {
+    let next_id = ids.len() as u32;
+    *ids.entry(key).or_insert(next_id)
+}
+
+impl LineAnalysis for MessageIndex {
+    /// Tag and message of an entry that has a message.
+    type Line = Option<(Option<String>, String)>;
+
+    fn parse_line(line: &str) -> Self::Line {
+        let log_entry = LogFileReader::parse_logline(line)?;
+        let message = text_of(&log_entry.object["message"])?;
+        Some((text_of(&log_entry.object["tag"]), message))
+    }
+
+    fn add_line(&mut self, _line_num: LineNumber, line: Self::Line) {
+        let keys = line.map(|(tag, message)| {
+            let message_id = id_of(&mut self.message_ids, message);
+            let tag_and_message_id = id_of(&mut self.tag_and_message_ids, (tag, message_id));
+            (message_id, tag_and_message_id)
+        });
+        self.keys.push(keys);
+    }
+}
+
+/// A run of consecutive rows with the same key, shown as one row unless expanded.
+pub struct Run {
+    pub first: LineNumber,
+    pub last: LineNumber,
+    pub len: usize,
+    key: Option<u32>,
+}
+
+/// Folds runs of consecutive repeated entries of a log table into one row.
+/// Like `LevelFilter`, lines are consumed incrementally as the analysis progresses.
+pub struct RepeatCollapser {
+    mode: CollapseMode,
+    runs: Vec<Run>,
+    /// Lines shown by the table: the first line of each collapsed run, and every
+    /// line of expanded runs.
+    rows: Vec<LineNumber>,
+    /// Replaced whenever `rows` is cleared.
+    rows_generation: usize,
+    /// Index into `runs` of the run each row belongs to.
+    row_runs: Vec<usize>,
+    /// First lines of the runs expanded by the user.
+    expanded: HashSet<LineNumber>,
+    lines_consumed: usize,
+    source_fingerprint: Option<SourceFingerprint>,
+    generation: usize,
+}
+
+impl RepeatCollapser {
+    pub fn new() -> Self {
+        Self {
+            mode: CollapseMode::Off,
+            runs: vec![],
+            rows: vec![],
+            rows_generation: next_lines_generation(),
+            row_runs: vec![],
+            expanded: HashSet::new(),
+            lines_consumed: 0,
+            source_fingerprint: None,
+            generation: 0,
+        }
+    }
+
+    pub fn mode(&self) -> CollapseMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: CollapseMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.expanded.clear();
+            self.reset();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.mode != CollapseMode::Off
+    }
+
+    /// Lines to show, or None if repeats aren't collapsed.
+    pub fn visible_lines(&self) -> Option<TableLines<'_>> {
+        self.is_active()
+            .then(|| TableLines::Lines(&self.rows, self.rows_generation))
+    }
+
+    /// The run shown in the given row, if the row is the first line of a repeated run.
+    pub fn run_at_row(&self, row: usize, line_num: LineNumber) -> Option<&Run> {
+        let run = &self.runs[*self.row_runs.get(row)?];
+        (run.len > 1 && run.first == line_num).then_some(run)
+    }
+
+    pub fn is_expanded(&self, run: &Run) -> bool {
+        self.expanded.contains(&run.first)
+    }
+
+    /// Expands a collapsed run, or collapses an expanded one.
+    pub fn toggle_expanded(&mut self, first_line: LineNumber) {
+        if !self.expanded.remove(&first_line) {
+            self.expanded.insert(first_line);
+        }
+        self.reset();
+    }
+
+    /// Groups the lines of `source` not seen yet into runs.
+    pub fn update(
+        &mut self,
+        source: &TableLines<'_>,
+        message_index: &BackgroundAnalysis<MessageIndex>,
+    ) {
+        if !self.is_active() {
+            return;
+        }
+        let index = message_index.lock();
+
+        let source_fingerprint = SourceFingerprint::of(source);
+        if message_index.generation() != self.generation
+            || Some(&source_fingerprint) != self.source_fingerprint.as_ref()
+            || source.len() < self.lines_consumed
+        {
+            self.reset();
+            self.generation = message_index.generation();
+            self.source_fingerprint = Some(source_fingerprint);
+        }
+
+        while self.lines_consumed < source.len() {
+            let line_num = source.get(self.lines_consumed);
+            let Some(key) = index.key(line_num, self.mode) else {
+                break;
+            };
+            self.lines_consumed += 1;
+
+            if let Some(run) = self.runs.last_mut() {
+                if key.is_some() && run.key == key {
+                    run.last = line_num;
+                    run.len += 1;
+                    if self.expanded.contains(&run.first) {
+                        self.rows.push(line_num);
+                        self.row_runs.push(self.runs.len() - 1);
+                    }
+                    continue;
+                }
+            }
+
+            self.runs.push(Run {
+                first: line_num,
+                last: line_num,
+                len: 1,
+                key,
+            });
+            self.rows.push(line_num);
+            self.row_runs.push(self.runs.len() - 1);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.runs.clear();
+        self.rows.clear();
+        self.rows_generation = next_lines_generation();
+        self.row_runs.clear();
+        self.lines_consumed = 0;
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use grep::matcher::Matcher;
+use grep::searcher::{sinks::Lossy, SearcherBuilder};
+use grep_regex::RegexMatcherBuilder;
+use regex::{Regex, RegexBuilder};
+
+use super::{
+    field_path::lookup_field,
+    log_file_reader::{LineIndex, LineNumber},
+    query::{Query, QueryError},
+};
+
+/// Matches found are sent to the UI at least this often.
+const SEND_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub enum SearchError {
+    IoError(io::Error),
+    GrepError(grep_regex::Error),
+    InvalidQuery(QueryError),
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::IoError(e) => write!(f, "{}", e),
+            SearchError::GrepError(e) => write!(f, "{}", e),
+            SearchError::InvalidQuery(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for SearchError {
+    fn from(value: io::Error) -> Self {
+        SearchError::IoError(value)
+    }
+}
+
+impl From<grep_regex::Error> for SearchError {
+    fn from(value: grep_regex::Error) -> Self {
+        SearchError::GrepError(value)
+    }
+}
+
+impl From<QueryError> for SearchError {
+    fn from(value: QueryError) -> Self {
+        SearchError::InvalidQuery(value)
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+    /// Search text is a field query (see `query`) instead of text matched against the line.
+    pub query: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            whole_word: false,
+            regex: false,
+            query: false,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Builds a regex matching the same text as a search with these options,
+    /// for highlighting matches. Returns None for field queries and invalid patterns.
+    pub fn highlight_regex(&self, search_text: &str) -> Option<Regex> {
+        if self.query || search_text.is_empty() {
+            return None;
+        }
+
+        let pattern = if self.regex {
+            search_text.to_string()
+        } else {
+            regex::escape(search_text)
+        };
+        let pattern = if self.whole_word {
+            format!(r"\b(?:{})\b", pattern)
+        } else {
+            pattern
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .ok()
+    }
+}
+
+enum SearchUpdate {
+    Matches(Vec<LineNumber>),
+    Finished(Result<(), SearchError>),
+}
+
+/// Shared between a `SearchJob` and its worker thread.
+#[derive(Default)]
+struct SearchProgress {
+    bytes_scanned: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+/// Reader that records how much of the file has been read,
+/// and reports the end of the file once the search is cancelled.
+struct ProgressReader<R> {
+    inner: R,
+    progress: Arc<SearchProgress>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.progress.cancelled.load(Ordering::Relaxed) {
+            return Ok(0);
+        }
+        let bytes_read = self.inner.read(buf)?;
+        self.progress
+            .bytes_scanned
+            .fetch_add(bytes_read as u64, Ordering::Relaxed);
+        Ok(bytes_read)
+    }
+}
+
+/// Collects matching line numbers and sends them to the UI in batches.
+struct MatchBatcher {
+    sender: Sender<SearchUpdate>,
+    batch: Vec<LineNumber>,
+    last_send: Instant,
+}
+
+impl MatchBatcher {
+    fn push(&mut self, line_num: LineNumber) {
+        self.batch.push(line_num);
+        if self.last_send.elapsed() >= SEND_INTERVAL {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.batch.is_empty() {
+            let _ = self
+                .sender
+                .send(SearchUpdate::Matches(std::mem::take(&mut self.batch)));
+        }
+        self.last_send = Instant::now();
+    }
+}
+
+/// A search running on a worker thread.
+/// Matching line numbers are streamed back in ascending order as they're found.
+/// The search is cancelled when the job is dropped.
+pub struct SearchJob {
+    receiver: Receiver<SearchUpdate>,
+    progress: Arc<SearchProgress>,
+    total_bytes: u64,
+    result: Option<Result<(), SearchError>>,
+}
+
+impl SearchJob {
+    /// Starts a text, regex or field query search of the lines from `first_line` on.
+    pub fn start(
+        options: &SearchOptions,
+        line_index: LineIndex,
+        first_line: LineNumber,
+        search_text: &str,
+        field: &str,
+    ) -> Self {
+        let options = options.clone();
+        let search_text = search_text.to_string();
+        let field = field.to_string();
+
+        Self::spawn(line_index, move |line_index, progress, batcher| {
+            if options.query {
+                let query = Query::parse(&search_text)?;
+                Self::filter_lines(line_index, first_line, progress, batcher, |_, line| {
+                    query.matches_line(line)
+                })
+            } else {
+                Self::search_text(
+                    &options,
+                    line_index,
+                    first_line,
+                    &search_text,
+                    &field,
+                    progress,
+                    batcher,
+                )
+            }
+        })
+    }
+
+    /// Starts a job returning every line from `first_line` on for which `predicate`
+    /// returns true. The predicate is called for each line in order.
+    pub fn start_line_filter(
+        line_index: LineIndex,
+        first_line: LineNumber,
+        predicate: impl FnMut(LineNumber, &str) -> bool + Send + 'static,
+    ) -> Self {
+        Self::spawn(line_index, move |line_index, progress, batcher| {
+            Self::filter_lines(line_index, first_line, progress, batcher, predicate)
+        })
+    }
+
+    fn spawn(
+        line_index: LineIndex,
+        search: impl FnOnce(&LineIndex, &Arc<SearchProgress>, &mut MatchBatcher) -> Result<(), SearchError>
+            + Send
+            + 'static,
+    ) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let progress = Arc::new(SearchProgress::default());
+        let total_bytes = line_index.file_size();
+
+        let worker_progress = progress.clone();
+        thread::spawn(move || {
+            let mut batcher = MatchBatcher {
+                sender: sender.clone(),
+                batch: vec![],
+                last_send: Instant::now(),
+            };
+            let result = search(&line_index, &worker_progress, &mut batcher);
+            batcher.flush();
+            let _ = sender.send(SearchUpdate::Finished(result));
+        });
+
+        Self {
+            receiver,
+            progress,
+            total_bytes,
+            result: None,
+        }
+    }
+
+    /// Searches the lines of the file from `first_line` on for ones matching the search text.
+    /// If `field` is not empty, only lines whose value at that field path matches are returned.
+    fn search_text(
+        options: &SearchOptions,
+        line_index: &LineIndex,
+        first_line: LineNumber,
+        search_text: &str,
+        field: &str,
+        progress: &Arc<SearchProgress>,
+        batcher: &mut MatchBatcher,
+    ) -> Result<(), SearchError> {
+        // If regex is turned off, escape the search text to literals.
+        let escaped_search_text = if !options.regex {
+            Some(regex::escape(search_text))
+        } else {
+            None
+        };
+
+        // Take a reference to escaped text (present if regex searching is off), or the search text if it's on.
+        let pattern = if let Some(ref escaped_text) = escaped_search_text {
+            escaped_text
+        } else {
+            search_text
+        };
+
+        // Build a grep matcher and searcher matching the options
+        let matcher = RegexMatcherBuilder::new()
+            .case_insensitive(!options.case_sensitive)
+            .word(options.whole_word)
+            .build(pattern)?;
+        let mut searcher = SearcherBuilder::new()
+            .encoding(line_index.encoding().grep_encoding())
+            .build();
+
+        // Only the lines of the index are searched, not ones appended since it was made.
+        let Some(start_offset) = line_index.line_start_offset(first_line) else {
+            return Ok(());
+        };
+        let mut file = File::open(line_index.path())?;
+        file.seek(SeekFrom::Start(start_offset))?;
+        progress
+            .bytes_scanned
+            .store(start_offset, Ordering::Relaxed);
+        let reader = line_index.encoding().grep_reader(ProgressReader {
+            inner: file.take(line_index.file_size() - start_offset),
+            progress: progress.clone(),
+        });
+
+        searcher.search_reader(
+            &matcher,
+            reader,
+            Lossy(|line_num, line| {
+                if progress.cancelled.load(Ordering::Relaxed) {
+                    return Ok(false);
+                }
+
+                if !field.is_empty() {
+                    let field_matches = match json::parse(line) {
+                        Ok(object) => {
+                            let value = lookup_field(&object, field);
+                            !value.is_null() && matcher.is_match(value.to_string().as_bytes())?
+                        }
+                        Err(_) => false,
+                    };
+                    if !field_matches {
+                        return Ok(true);
+                    }
+                }
+
+                let zero_based_line_num = first_line + line_num as LineNumber - 1;
+                batcher.push(zero_based_line_num);
+                Ok(true)
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    /// Evaluates `predicate` against every line of the file from `first_line` on.
+    fn filter_lines(
+        line_index: &LineIndex,
+        first_line: LineNumber,
+        progress: &Arc<SearchProgress>,
+        batcher: &mut MatchBatcher,
+        mut predicate: impl FnMut(LineNumber, &str) -> bool,
+    ) -> Result<(), SearchError> {
+        line_index.for_each_line(first_line..line_index.line_count(), |line_num, line| {
+            if predicate(line_num, line) {
+                batcher.push(line_num);
+            }
+            if let Some(line_end) = line_index.line_start_offset(line_num + 1) {
+                progress.bytes_scanned.store(line_end, Ordering::Relaxed);
+            }
+            !progress.cancelled.load(Ordering::Relaxed)
+        })?;
+
+        Ok(())
+    }
+
+    /// Appends any matches received since the last poll to `results`, keeping it sorted.
+    pub fn poll(&mut self, results: &mut Vec<LineNumber>) {
+        while let Ok(update) = self.receiver.try_recv() {
+            match update {
+                SearchUpdate::Matches(matches) => {
+                    let in_order = match (results.last(), matches.first()) {
+                        (Some(last), Some(first)) => last < first,
+                        _ => true,
+                    };
+                    results.extend(matches);
+                    if !in_order {
+                        results.sort_unstable();
+                        results.dedup();
+                    }
+                }
+                SearchUpdate::Finished(result) => self.result = Some(result),
+            }
+        }
+    }
+
+    /// Bytes of the file searched so far and the total size of the file.
+    pub fn progress(&self) -> (u64, u64) {
+        (
+            self.progress
+                .bytes_scanned
+                .load(Ordering::Relaxed)
+                .min(self.total_bytes),
+            self.total_bytes,
+        )
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+
+    pub fn error(&self) -> Option<&SearchError> {
+        self.result.as_ref()?.as_ref().err()
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SearchJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::search_job::SearchOptions;
+
+/// Number of recent searches kept in the history.
+const MAX_HISTORY_LEN: usize = 30;
+
+/// The text, field and options of a search, enough to run it again.
+#[derive(Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SearchParams {
+    pub term: String,
+    pub field: String,
+    pub options: SearchOptions,
+}
+
+impl SearchParams {
+    /// Describes the search in one line, e.g. `"timeout" in message (Case, Regex)`.
+    pub fn summary(&self) -> String {
+        let mut summary = if self.options.query {
+            format!("Query: {}", self.term)
+        } else {
+            format!("\"{}\"", self.term)
+        };
+        if !self.options.query && !self.field.is_empty() {
+            summary += &format!(" in {}", self.field);
+        }
+
+        let flags: Vec<&str> = [
+            (self.options.case_sensitive, "Case"),
+            (self.options.whole_word, "Word"),
+            (self.options.regex, "Regex"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled && !self.options.query)
+        .map(|(_, name)| *name)
+        .collect();
+        if !flags.is_empty() {
+            summary += &format!(" ({})", flags.join(", "));
+        }
+        summary
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub params: SearchParams,
+}
+
+/// Recently executed searches and searches saved by name, persisted with the app.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SearchLibrary {
+    /// Most recent first.
+    history: Vec<SearchParams>,
+    saved: Vec<SavedSearch>,
+}
+
+/// The library is shared by the search tabs of every open file.
+pub type SharedSearchLibrary = Rc<RefCell<SearchLibrary>>;
+
+impl SearchLibrary {
+    pub fn history(&self) -> &[SearchParams] {
+        &self.history
+    }
+
+    pub fn saved(&self) -> &[SavedSearch] {
+        &self.saved
+    }
+
+    /// Adds a search to the top of the history, removing an earlier identical one.
+    pub fn record(&mut self, params: SearchParams) {
+        if params.term.is_empty() {
+            return;
+        }
+        self.history.retain(|p| *p != params);
+        self.history.insert(0, params);
+        self.history.truncate(MAX_HISTORY_LEN);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Saves a search by name, replacing a saved search of the same name.
+    pub fn save(&mut self, name: &str, params: SearchParams) {
+        let saved_search = SavedSearch {
+            name: name.trim().to_string(),
+            params,
+        };
+        match self.saved.iter_mut().find(|s| s.name == saved_search.name) {
+            Some(existing) => *existing = saved_search,
+            None => self.saved.push(saved_search),
+        }
+    }
+
+    pub fn remove_saved(&mut self, name: &str) {
+        self.saved.retain(|s| s.name != name);
+    }
+}
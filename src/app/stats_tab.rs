@@ -0,0 +1,341 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Duration;
+
+use egui::{Color32, CursorIcon, RichText, Ui};
+
+use super::{
+    filtered_log_entries_tab::format_bytes,
+    level_filter::LogLevel,
+    log_analysis::LineAnalysis,
+    log_entries_table::color_from_loglevel,
+    log_file_reader::{FileOffset, LineNumber, LogFileReader},
+    log_view::{LogViewTabTrait, LogViewerState},
+    timestamp::{format_duration, format_timestamp, Timestamp, MINUTE_MS, SECOND_MS},
+};
+
+/// Number of tags listed, most frequent first.
+const TOP_TAG_COUNT: usize = 10;
+
+/// Number of lines listed, longest first.
+const LARGEST_LINE_COUNT: usize = 10;
+
+/// Characters of a line previewed next to its size.
+const LINE_PREVIEW_LEN: usize = 120;
+
+/// Summary statistics of a log file.
+#[derive(Default)]
+pub struct LogStats {
+    line_count: usize,
+    parse_failures: usize,
+    first_time: Option<Timestamp>,
+    last_time: Option<Timestamp>,
+    level_counts: [usize; LogLevel::ALL.len()],
+    tag_counts: HashMap<String, usize>,
+    /// Entries with a timestamp, per minute since the epoch.
+    minute_counts: HashMap<i64, usize>,
+    timestamped_entries: usize,
+    /// Busiest minute and its number of entries.
+    peak_minute: Option<(i64, usize)>,
+    /// Longest lines seen so far as (length in bytes, line), shortest on top.
+    largest_lines: BinaryHeap<Reverse<(usize, LineNumber)>>,
+}
+
+impl LogStats {
+    fn entry_count(&self) -> usize {
+        self.line_count - self.parse_failures
+    }
+
+    fn duration(&self) -> Option<i64> {
+        Some(self.last_time? - self.first_time?)
+    }
+
+    /// Mean number of timestamped entries per minute over the span of the log.
+    fn average_rate(&self) -> Option<f64> {
+        let duration = self.duration().filter(|&duration| duration > 0)?;
+        Some(self.timestamped_entries as f64 / (duration as f64 / MINUTE_MS as f64))
+    }
+
+    fn top_tags(&self) -> Vec<(&str, usize)> {
+        let mut tags: Vec<(&str, usize)> = self
+            .tag_counts
+            .iter()
+            .map(|(tag, count)| (tag.as_str(), *count))
+            .collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        tags.truncate(TOP_TAG_COUNT);
+        tags
+    }
+
+    /// Longest lines as (length in bytes, line), longest first.
+    fn largest_lines(&self) -> Vec<(usize, LineNumber)> {
+        let mut lines: Vec<(usize, LineNumber)> = self
+            .largest_lines
+            .iter()
+            .map(|Reverse(line)| *line)
+            .collect();
+        lines.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        lines
+    }
+}
+
+/// What `LogStats` needs of a line.
+pub struct StatsLine {
+    /// Length in bytes without the line terminator.
+    len: usize,
+    /// Level, tag and time of the entry, if the line is one.
+    entry: Option<(LogLevel, Option<String>, Option<Timestamp>)>,
+}
+
+impl LineAnalysis for LogStats {
+    type Line = StatsLine;
+
+    fn parse_line(line: &str) -> StatsLine {
+        let entry = LogFileReader::parse_logline(line).map(|log_entry| {
+            let level = log_entry.object["level"]
+                .as_str()
+                .map_or(LogLevel::Other, LogLevel::from_name);
+            let tag = log_entry.object["tag"].as_str().map(str::to_string);
+            (level, tag, log_entry.time())
+        });
+        StatsLine {
+            len: line.trim_end_matches(['\r', '\n']).len(),
+            entry,
+        }
+    }
+
+    fn add_line(&mut self, line_num: LineNumber, line: StatsLine) {
+        self.line_count += 1;
+
+        self.largest_lines.push(Reverse((line.len, line_num)));
+        if self.largest_lines.len() > LARGEST_LINE_COUNT {
+            self.largest_lines.pop();
+        }
+
+        let Some((level, tag, time)) = line.entry else {
+            self.parse_failures += 1;
+            return;
+        };
+
+        self.level_counts[level.index()] += 1;
+
+        if let Some(tag) = tag {
+            *self.tag_counts.entry(tag).or_default() += 1;
+        }
+
+        if let Some(time) = time {
+            self.first_time = Some(self.first_time.map_or(time, |t| t.min(time)));
+            self.last_time = Some(self.last_time.map_or(time, |t| t.max(time)));
+            self.timestamped_entries += 1;
+
+            let minute = time.div_euclid(MINUTE_MS);
+            let count = self.minute_counts.entry(minute).or_default();
+            *count += 1;
+            if self.peak_minute.map_or(true, |(_, peak)| *count > peak) {
+                self.peak_minute = Some((minute, *count));
+            }
+        }
+    }
+}
+
+/// `count` as a percentage of `total`, e.g. "12.5%".
+pub fn percentage(count: usize, total: usize) -> String {
+    format!("{:.1}%", count as f64 * 100.0 / total.max(1) as f64)
+}
+
+/// Shows statistics of the whole file, kept up to date as it grows.
+pub struct StatsTab {}
+
+impl StatsTab {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+
+    fn section(ui: &mut Ui, title: &str, add_rows: impl FnOnce(&mut Ui)) {
+        ui.add_space(8.0);
+        ui.label(RichText::new(title).strong());
+        ui.separator();
+        egui::Grid::new(title)
+            .num_columns(2)
+            .spacing([24.0, 4.0])
+            .striped(true)
+            .show(ui, add_rows);
+    }
+
+    fn row(ui: &mut Ui, name: &str, value: impl Into<egui::WidgetText>) {
+        ui.label(name);
+        ui.label(value);
+        ui.end_row();
+    }
+
+    fn ui_file(ui: &mut Ui, stats: &LogStats, file_size: FileOffset) {
+        Self::section(ui, "File", |ui| {
+            Self::row(ui, "Lines", stats.line_count.to_string());
+            Self::row(ui, "Size", format_bytes(file_size));
+            Self::row(ui, "Log entries", stats.entry_count().to_string());
+            let failures = RichText::new(format!(
+                "{} ({})",
+                stats.parse_failures,
+                percentage(stats.parse_failures, stats.line_count)
+            ));
+            let failures = if stats.parse_failures > 0 {
+                failures.color(ui.visuals().warn_fg_color)
+            } else {
+                failures
+            };
+            Self::row(ui, "Parse failures", failures);
+        });
+    }
+
+    fn ui_time(ui: &mut Ui, stats: &LogStats) {
+        Self::section(ui, "Time", |ui| {
+            let time_text = |time: Option<Timestamp>| time.map_or("-".into(), format_timestamp);
+            Self::row(ui, "First entry", time_text(stats.first_time));
+            Self::row(ui, "Last entry", time_text(stats.last_time));
+            Self::row(
+                ui,
+                "Duration",
+                stats.duration().map_or("-".into(), format_duration),
+            );
+            Self::row(
+                ui,
+                "Average rate",
+                stats.average_rate().map_or("-".into(), |rate| {
+                    format!(
+                        "{:.1} entries/min ({:.2}/s)",
+                        rate,
+                        rate * SECOND_MS as f64 / MINUTE_MS as f64
+                    )
+                }),
+            );
+            Self::row(
+                ui,
+                "Peak rate",
+                stats.peak_minute.map_or("-".into(), |(minute, count)| {
+                    format!(
+                        "{} entries/min at {}",
+                        count,
+                        format_timestamp(minute * MINUTE_MS)
+                    )
+                }),
+            );
+        });
+    }
+
+    fn ui_levels(ui: &mut Ui, stats: &LogStats) {
+        Self::section(ui, "Levels", |ui| {
+            for level in LogLevel::ALL {
+                let count = stats.level_counts[level.index()];
+                ui.label(RichText::new(level.name()).color(color_from_loglevel(level.name())));
+                ui.label(format!(
+                    "{} ({})",
+                    count,
+                    percentage(count, stats.entry_count())
+                ));
+                ui.end_row();
+            }
+        });
+    }
+
+    fn ui_tags(ui: &mut Ui, stats: &LogStats) {
+        Self::section(ui, "Top Tags", |ui| {
+            let top_tags = stats.top_tags();
+            if top_tags.is_empty() {
+                Self::row(ui, "No tags", "");
+            }
+            for (tag, count) in top_tags {
+                ui.label(RichText::new(tag).monospace());
+                ui.label(format!(
+                    "{} ({})",
+                    count,
+                    percentage(count, stats.entry_count())
+                ));
+                ui.end_row();
+            }
+            if stats.tag_counts.len() > TOP_TAG_COUNT {
+                Self::row(
+                    ui,
+                    "",
+                    RichText::new(format!("{} tags in total", stats.tag_counts.len())).weak(),
+                );
+            }
+        });
+    }
+
+    /// Lists the longest lines. Returns the line clicked, if any.
+    fn ui_largest_lines(
+        ui: &mut Ui,
+        stats: &LogStats,
+        log_reader: &mut LogFileReader,
+    ) -> Option<LineNumber> {
+        let mut clicked_line = None;
+        Self::section(ui, "Largest Lines", |ui| {
+            for (line_len, line_num) in stats.largest_lines() {
+                if ui
+                    .link(format!("Line {}", line_num + 1))
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_text("Select Line")
+                    .clicked()
+                {
+                    clicked_line = Some(line_num);
+                }
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format_bytes(line_len as u64)).monospace());
+                    if line_num < log_reader.line_count() {
+                        let line = log_reader.read_line(line_num).unwrap_or_default();
+                        let preview: String = line.trim().chars().take(LINE_PREVIEW_LEN).collect();
+                        ui.label(RichText::new(preview).monospace().color(Color32::GRAY));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+        clicked_line
+    }
+}
+
+impl LogViewTabTrait for StatsTab {
+    fn title(&self) -> egui::WidgetText {
+        "📈 Stats".into()
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        viewer_state: &mut LogViewerState,
+    ) {
+        viewer_state.stats.sync(log_reader);
+
+        ui.horizontal(|ui| {
+            let (lines_analyzed, line_count) = viewer_state.stats.progress();
+            if viewer_state.stats.is_complete() {
+                ui.label(format!("{} lines analyzed", lines_analyzed));
+            } else {
+                ui.spinner();
+                ui.label(format!(
+                    "Analyzed {} of {} lines",
+                    lines_analyzed, line_count
+                ));
+                ui.ctx().request_repaint_after(Duration::from_millis(250));
+            }
+        });
+        ui.separator();
+
+        let mut clicked_line = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .show(ui, |ui| {
+                let stats = viewer_state.stats.lock();
+                Self::ui_file(ui, &stats, log_reader.line_index().file_size());
+                Self::ui_time(ui, &stats);
+                Self::ui_levels(ui, &stats);
+                Self::ui_tags(ui, &stats);
+                clicked_line = Self::ui_largest_lines(ui, &stats, log_reader);
+            });
+
+        if clicked_line.is_some() {
+            viewer_state.selected_line_num = clicked_line;
+        }
+    }
+}
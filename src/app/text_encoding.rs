@@ -0,0 +1,157 @@
+use std::io::{self, Read};
+
+use grep::searcher::Encoding;
+
+/// Characters for bytes 0x80-0x9F in Windows-1252. The remaining bytes map to the
+/// same code points as ISO-8859-1. Undefined bytes map to the C1 control of the same
+/// value, like grep's decoder does, so that search and display agree.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// Text encoding of a log file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Windows1252,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 5] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::Latin1,
+        TextEncoding::Windows1252,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::Latin1 => "Latin-1 (ISO-8859-1)",
+            TextEncoding::Windows1252 => "Windows-1252",
+        }
+    }
+
+    /// Detects the encoding from the byte order mark at the start of a file, if any.
+    pub fn from_bom(bytes: &[u8]) -> Option<TextEncoding> {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Some(TextEncoding::Utf8),
+            [0xFF, 0xFE, ..] => Some(TextEncoding::Utf16Le),
+            [0xFE, 0xFF, ..] => Some(TextEncoding::Utf16Be),
+            _ => None,
+        }
+    }
+
+    /// Size in bytes of a single code unit. Newlines are a single code unit in every
+    /// supported encoding.
+    pub fn code_unit_size(&self) -> usize {
+        match self {
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns true if the code unit at the start of `bytes` is a line feed.
+    pub fn is_newline(&self, bytes: &[u8]) -> bool {
+        match self {
+            TextEncoding::Utf16Le => bytes.starts_with(&[b'\n', 0]),
+            TextEncoding::Utf16Be => bytes.starts_with(&[0, b'\n']),
+            _ => bytes.first() == Some(&b'\n'),
+        }
+    }
+
+    /// Decodes bytes in this encoding, replacing invalid sequences with the
+    /// replacement character. A leading byte order mark is removed.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let mut text = match self {
+            TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            TextEncoding::Utf16Le => Self::decode_utf16(bytes, u16::from_le_bytes),
+            TextEncoding::Utf16Be => Self::decode_utf16(bytes, u16::from_be_bytes),
+            TextEncoding::Latin1 => bytes.iter().map(|b| *b as char).collect(),
+            TextEncoding::Windows1252 => bytes
+                .iter()
+                .map(|b| match b {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+                    _ => *b as char,
+                })
+                .collect(),
+        };
+
+        if text.starts_with(BYTE_ORDER_MARK) {
+            text.drain(..BYTE_ORDER_MARK.len_utf8());
+        }
+        text
+    }
+
+    fn decode_utf16(bytes: &[u8], to_code_unit: fn([u8; 2]) -> u16) -> String {
+        let code_units = bytes
+            .chunks_exact(2)
+            .map(|pair| to_code_unit([pair[0], pair[1]]));
+        char::decode_utf16(code_units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+
+    /// The encoding grep should transcode from when searching the file.
+    /// UTF-8 is searched as-is, and Latin-1 is transcoded by `grep_reader`.
+    pub fn grep_encoding(&self) -> Option<Encoding> {
+        let label = match self {
+            TextEncoding::Utf8 | TextEncoding::Latin1 => return None,
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Windows1252 => "windows-1252",
+        };
+        Encoding::new(label).ok()
+    }
+
+    /// Wraps a reader of the file for grep to search with `grep_encoding`.
+    /// grep's "iso-8859-1" is really Windows-1252, so Latin-1 is transcoded to UTF-8 here.
+    pub fn grep_reader<'a>(&self, reader: impl Read + 'a) -> Box<dyn Read + 'a> {
+        match self {
+            TextEncoding::Latin1 => Box::new(Latin1Reader {
+                inner: reader,
+                raw: vec![],
+                pending: vec![],
+            }),
+            _ => Box::new(reader),
+        }
+    }
+}
+
+/// Transcodes ISO-8859-1 to UTF-8 while reading.
+struct Latin1Reader<R> {
+    inner: R,
+    /// Bytes read from `inner`.
+    raw: Vec<u8>,
+    /// Transcoded bytes that didn't fit in the caller's buffer yet.
+    pending: Vec<u8>,
+}
+
+impl<R: Read> Read for Latin1Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            // Every byte takes at most two bytes in UTF-8.
+            self.raw.resize((buf.len() / 2).max(1), 0);
+            let count = self.inner.read(&mut self.raw)?;
+            for &byte in &self.raw[..count] {
+                let mut utf8 = [0; 2];
+                let utf8 = (byte as char).encode_utf8(&mut utf8);
+                self.pending.extend_from_slice(utf8.as_bytes());
+            }
+        }
+        let count = self.pending.len().min(buf.len());
+        buf[..count].copy_from_slice(&self.pending[..count]);
+        self.pending.drain(..count);
+        Ok(count)
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::BTreeMap;
+
+use egui::{Align2, Color32, FontId, Rect, Response, RichText, Sense, Stroke, Ui};
+
+use super::{
+    level_filter::LogLevel,
+    log_analysis::{BackgroundAnalysis, LineAnalysis},
+    log_entries_table::{color_from_loglevel, TableLines},
+    log_file_reader::{LineNumber, LogFileReader},
+    timestamp::{format_timestamp, Timestamp, DAY_MS, HOUR_MS, MINUTE_MS, SECOND_MS},
+};
+
+const HISTOGRAM_HEIGHT: f32 = 40.0;
+
+const LABEL_HEIGHT: f32 = 12.0;
+
+/// Narrowest a bar is drawn. Buckets are made longer until they fit.
+const MIN_BAR_WIDTH: f32 = 3.0;
+
+/// Bucket lengths to choose from, shortest first.
+const BUCKET_SIZES: [i64; 20] = [
+    SECOND_MS,
+    2 * SECOND_MS,
+    5 * SECOND_MS,
+    10 * SECOND_MS,
+    15 * SECOND_MS,
+    30 * SECOND_MS,
+    MINUTE_MS,
+    2 * MINUTE_MS,
+    5 * MINUTE_MS,
+    10 * MINUTE_MS,
+    15 * MINUTE_MS,
+    30 * MINUTE_MS,
+    HOUR_MS,
+    2 * HOUR_MS,
+    3 * HOUR_MS,
+    6 * HOUR_MS,
+    12 * HOUR_MS,
+    DAY_MS,
+    2 * DAY_MS,
+    7 * DAY_MS,
+];
+
+/// Levels from the bottom of a bar to the top, most severe first so they stay visible.
+const STACK_ORDER: [LogLevel; 6] = [
+    LogLevel::Fatal,
+    LogLevel::Error,
+    LogLevel::Warning,
+    LogLevel::Info,
+    LogLevel::Debug,
+    LogLevel::Other,
+];
+
+type LevelCounts = [u32; LogLevel::ALL.len()];
+
+/// Number of entries of each level in every second of a log file.
+#[derive(Default)]
+pub struct VolumeIndex {
+    seconds: BTreeMap<i64, LevelCounts>,
+}
+
+impl LineAnalysis for VolumeIndex {
+    /// Time and level of a timestamped entry.
+    type Line = Option<(Timestamp, LogLevel)>;
+
+    fn parse_line(line: &str) -> Self::Line {
+        let log_entry = LogFileReader::parse_logline(line)?;
+        let time = log_entry.time()?;
+        let level = log_entry.object["level"]
+            .as_str()
+            .map_or(LogLevel::Other, LogLevel::from_name);
+        Some((time, level))
+    }
+
+    fn add_line(&mut self, _line_num: LineNumber, line: Self::Line) {
+        let Some((time, level)) = line else {
+            return;
+        };
+        self.seconds.entry(time.div_euclid(SECOND_MS)).or_default()[level.index()] += 1;
+    }
+}
+
+/// Shortest bucket length that splits `span` into at most `max_buckets` buckets.
+fn bucket_size(span: i64, max_buckets: usize) -> i64 {
+    let max_buckets = max_buckets.max(1) as i64;
+    BUCKET_SIZES
+        .into_iter()
+        .find(|&size| span / size < max_buckets)
+        .unwrap_or_else(|| (span / max_buckets / DAY_MS + 1) * DAY_MS)
+}
+
+/// Formats a bucket length as e.g. `30s`, `5m` or `2d`.
+fn format_bucket_size(size: i64) -> String {
+    if size % DAY_MS == 0 {
+        format!("{}d", size / DAY_MS)
+    } else if size % HOUR_MS == 0 {
+        format!("{}h", size / HOUR_MS)
+    } else if size % MINUTE_MS == 0 {
+        format!("{}m", size / MINUTE_MS)
+    } else {
+        format!("{}s", size / SECOND_MS)
+    }
+}
+
+/// Entry counts of consecutive buckets of equal length.
+struct Buckets {
+    start: Timestamp,
+    size: i64,
+    counts: Vec<LevelCounts>,
+    max_total: u32,
+}
+
+impl Buckets {
+    fn of(volume: &VolumeIndex, max_buckets: usize) -> Option<Self> {
+        let first_second = *volume.seconds.keys().next()?;
+        let last_second = *volume.seconds.keys().next_back()?;
+        let span = (last_second - first_second + 1) * SECOND_MS;
+        let size = bucket_size(span, max_buckets);
+        let start = (first_second * SECOND_MS).div_euclid(size) * size;
+        let bucket_count = ((last_second * SECOND_MS - start) / size + 1) as usize;
+
+        let mut counts = vec![LevelCounts::default(); bucket_count];
+        for (second, second_counts) in &volume.seconds {
+            let bucket = &mut counts[((second * SECOND_MS - start) / size) as usize];
+            for (count, second_count) in bucket.iter_mut().zip(second_counts) {
+                *count += second_count;
+            }
+        }
+        let max_total = counts
+            .iter()
+            .map(|c| c.iter().sum::<u32>())
+            .max()
+            .unwrap_or_default();
+
+        Some(Self {
+            start,
+            size,
+            counts,
+            max_total,
+        })
+    }
+
+    fn end(&self) -> Timestamp {
+        self.start + self.size * self.counts.len() as i64
+    }
+}
+
+/// What the user did with the histogram.
+pub enum HistogramAction {
+    /// Clicked the given time.
+    JumpTo(Timestamp),
+    /// Dragged over the given time range.
+    SelectRange(Timestamp, Timestamp),
+}
+
+/// A strip showing the number of entries over time, stacked by level.
+#[derive(Default)]
+pub struct TimeHistogram {
+    buckets: Option<Buckets>,
+    /// Analysis generation, lines analyzed and bucket limit the buckets were computed for.
+    computed_for: Option<(usize, usize, usize)>,
+    drag_start: Option<Timestamp>,
+}
+
+impl TimeHistogram {
+    fn update(&mut self, volume: &BackgroundAnalysis<VolumeIndex>, max_buckets: usize) {
+        let key = (volume.generation(), volume.progress().0, max_buckets);
+        if self.computed_for != Some(key) {
+            self.computed_for = Some(key);
+            self.buckets = Buckets::of(&volume.lock(), max_buckets);
+        }
+    }
+
+    /// Shows the histogram. `viewport` is the time span of the rows in view, and
+    /// `selected_range` the bounds of the time range filter.
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        volume: &BackgroundAnalysis<VolumeIndex>,
+        viewport: Option<(Timestamp, Timestamp)>,
+        selected_range: Option<(Option<Timestamp>, Option<Timestamp>)>,
+    ) -> Option<HistogramAction> {
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), HISTOGRAM_HEIGHT),
+            Sense::click_and_drag(),
+        );
+        let (label_rect, _) =
+            ui.allocate_exact_size(egui::vec2(rect.width(), LABEL_HEIGHT), Sense::hover());
+        ui.separator();
+
+        self.update(volume, (rect.width() / MIN_BAR_WIDTH) as usize);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let Some(ref buckets) = self.buckets else {
+            let text = if volume.is_complete() {
+                "No timestamped entries"
+            } else {
+                "Counting entries…"
+            };
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                text,
+                FontId::proportional(11.0),
+                ui.visuals().weak_text_color(),
+            );
+            return None;
+        };
+
+        let span = (buckets.end() - buckets.start) as f32;
+        let x_of =
+            |time: Timestamp| rect.left() + (time - buckets.start) as f32 / span * rect.width();
+        let time_at = |x: f32| {
+            buckets.start + ((x - rect.left()) / rect.width() * span).clamp(0.0, span) as i64
+        };
+        let bar_width = rect.width() / buckets.counts.len() as f32;
+
+        for (index, counts) in buckets.counts.iter().enumerate() {
+            let left = rect.left() + index as f32 * bar_width;
+            let mut bottom = rect.bottom();
+            for level in STACK_ORDER {
+                let count = counts[level.index()];
+                if count == 0 {
+                    continue;
+                }
+                let height = count as f32 / buckets.max_total as f32 * rect.height();
+                let segment = Rect::from_min_max(
+                    egui::pos2(left, bottom - height),
+                    egui::pos2((left + bar_width - 1.0).max(left + 1.0), bottom),
+                );
+                painter.rect_filled(segment, 0.0, color_from_loglevel(level.name()));
+                bottom -= height;
+            }
+        }
+
+        if let Some((start, end)) = selected_range {
+            let left = start.map_or(rect.left(), x_of);
+            let right = end.map_or(rect.right(), x_of);
+            Self::paint_range(ui, rect, left, right);
+        }
+
+        if let Some((first, last)) = viewport {
+            let left = x_of(first);
+            let right = x_of(last).max(left + 2.0);
+            let viewport_rect = Rect::from_x_y_ranges(left..=right, rect.y_range());
+            painter.rect(
+                viewport_rect,
+                0.0,
+                Color32::from_white_alpha(16),
+                Stroke::new(1.0, ui.visuals().strong_text_color()),
+            );
+        }
+
+        let label_font = FontId::proportional(10.0);
+        let label_color = ui.visuals().weak_text_color();
+        let label_painter = ui.painter_at(label_rect);
+        label_painter.text(
+            label_rect.left_center(),
+            Align2::LEFT_CENTER,
+            format_timestamp(buckets.start),
+            label_font.clone(),
+            label_color,
+        );
+        label_painter.text(
+            label_rect.center(),
+            Align2::CENTER_CENTER,
+            format!("1 bar = {}", format_bucket_size(buckets.size)),
+            label_font.clone(),
+            label_color,
+        );
+        label_painter.text(
+            label_rect.right_center(),
+            Align2::RIGHT_CENTER,
+            format_timestamp(buckets.end()),
+            label_font,
+            label_color,
+        );
+
+        let mut action = None;
+        let pointer_time = response.interact_pointer_pos().map(|pos| time_at(pos.x));
+        if response.drag_started() {
+            self.drag_start = pointer_time;
+        }
+        if let (Some(drag_start), Some(pointer_time)) = (self.drag_start, pointer_time) {
+            Self::paint_range(ui, rect, x_of(drag_start), x_of(pointer_time));
+        }
+        if response.drag_stopped() {
+            if let (Some(drag_start), Some(pointer_time)) = (self.drag_start.take(), pointer_time) {
+                if (x_of(drag_start) - x_of(pointer_time)).abs() >= MIN_BAR_WIDTH {
+                    action = Some(HistogramAction::SelectRange(
+                        drag_start.min(pointer_time),
+                        drag_start.max(pointer_time),
+                    ));
+                }
+            }
+        } else if response.clicked() {
+            action = pointer_time.map(HistogramAction::JumpTo);
+        }
+
+        if self.drag_start.is_none() {
+            Self::hover_ui(response, buckets, bar_width, rect);
+        }
+        action
+    }
+
+    fn paint_range(ui: &Ui, rect: Rect, left: f32, right: f32) {
+        let range_rect = Rect::from_x_y_ranges(left.min(right)..=left.max(right), rect.y_range());
+        ui.painter().rect_filled(
+            range_rect.intersect(rect),
+            0.0,
+            ui.visuals().selection.bg_fill.gamma_multiply(0.4),
+        );
+    }
+
+    /// Shows the time and counts of the bucket under the pointer.
+    fn hover_ui(response: Response, buckets: &Buckets, bar_width: f32, rect: Rect) {
+        let Some(pos) = response.hover_pos() else {
+            return;
+        };
+        let index = (((pos.x - rect.left()) / bar_width) as usize).min(buckets.counts.len() - 1);
+        let bucket_start = buckets.start + buckets.size * index as i64;
+        let counts = buckets.counts[index];
+
+        response
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_ui_at_pointer(|ui| {
+                ui.label(format!(
+                    "{} to {}",
+                    format_timestamp(bucket_start),
+                    format_timestamp(bucket_start + buckets.size)
+                ));
+                ui.label(RichText::new(format!("{} entries", counts.iter().sum::<u32>())).strong());
+                for level in STACK_ORDER.iter().rev() {
+                    let count = counts[level.index()];
+                    if count > 0 {
+                        ui.label(
+                            RichText::new(format!("{}: {}", level.name(), count))
+                                .color(color_from_loglevel(level.name())),
+                        );
+                    }
+                }
+                ui.label(RichText::new("Click to jump, drag to set the time range").weak());
+            });
+    }
+}
+
+/// Finds the first row of `lines` at or after `time`, assuming timestamps are in
+/// ascending order. Rows without a timestamp are grouped with the next row that has one.
+pub fn row_at_time(
+    log_reader: &mut LogFileReader,
+    lines: &TableLines<'_>,
+    time: Timestamp,
+) -> Option<usize> {
+    if lines.is_empty() {
+        return None;
+    }
+    let (mut low, mut high) = (0, lines.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let next_timestamp = (mid..high).find_map(|row| {
+            let timestamp = log_reader.read_entry(lines.get(row))?.time()?;
+            Some((row, timestamp))
+        });
+        match next_timestamp {
+            Some((row, timestamp)) if timestamp < time => low = row + 1,
+            _ => high = mid,
+        }
+    }
+    Some(low.min(lines.len() - 1))
+}
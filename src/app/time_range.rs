@@ -0,0 +1,372 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use egui::{RichText, Ui};
+use log::error;
+
+use super::{
+    log_analysis::{BackgroundAnalysis, LineAnalysis},
+    log_entries_table::next_lines_generation,
+    log_file_reader::{LineNumber, LogFileReader},
+    search_job::SearchJob,
+    timestamp::{
+        format_timestamp, parse_duration, parse_time_of_day, parse_timestamp, Timestamp, DAY_MS,
+    },
+};
+
+/// Checks whether the timestamps of a log file are in ascending order.
+#[derive(Default)]
+pub struct TimestampOrder {
+    latest: Option<Timestamp>,
+    out_of_order_lines: usize,
+    first: Option<Timestamp>,
+    last: Option<Timestamp>,
+}
+
+impl TimestampOrder {
+    /// Number of lines with a timestamp earlier than a line before them.
+    pub fn out_of_order_lines(&self) -> usize {
+        self.out_of_order_lines
+    }
+
+    /// Timestamps of the first and last lines that have one, among the lines analyzed so far.
+    pub fn bounds(&self) -> (Option<Timestamp>, Option<Timestamp>) {
+        (self.first, self.last)
+    }
+}
+
+impl LineAnalysis for TimestampOrder {
+    type Line = Option<Timestamp>;
+
+    fn parse_line(line: &str) -> Option<Timestamp> {
+        LogFileReader::parse_logline(line).and_then(|e| e.time())
+    }
+
+    fn add_line(&mut self, _line_num: LineNumber, timestamp: Option<Timestamp>) {
+        let Some(timestamp) = timestamp else {
+            return;
+        };
+        self.first.get_or_insert(timestamp);
+        self.last = Some(timestamp);
+        match self.latest {
+            Some(latest) if timestamp < latest => self.out_of_order_lines += 1,
+            _ => self.latest = Some(timestamp),
+        }
+    }
+}
+
+/// Lines found to be within the time range.
+#[derive(Clone)]
+pub enum RangeLines {
+    /// The file is ordered by time, so the range is a contiguous block of lines.
+    Contiguous(Range<LineNumber>),
+    /// Lines found by scanning the whole file, used when timestamps are out of order.
+    Scanned(Arc<Vec<LineNumber>>),
+}
+
+impl RangeLines {
+    pub fn len(&self) -> usize {
+        match self {
+            RangeLines::Contiguous(range) => range.len(),
+            RangeLines::Scanned(lines) => lines.len(),
+        }
+    }
+}
+
+/// Restricts the tables of a log view to entries within a time range.
+#[derive(Default)]
+pub struct TimeRangeFilter {
+    editable_start: String,
+    editable_end: String,
+    bounds: Option<(Option<Timestamp>, Option<Timestamp>)>,
+    lines: Option<RangeLines>,
+    scan_job: Option<SearchJob>,
+    error: Option<String>,
+    /// Load of the file the lines were found for.
+    load_time: Option<SystemTime>,
+    /// Changes whenever the lines are recomputed, see `next_lines_generation`.
+    generation: usize,
+}
+
+impl TimeRangeFilter {
+    /// Lines within the range, or None if no range is set.
+    pub fn lines(&self) -> Option<RangeLines> {
+        self.lines.clone()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.bounds.is_some()
+    }
+
+    /// Start and end of the range, or None if no range is set.
+    pub fn bounds(&self) -> Option<(Option<Timestamp>, Option<Timestamp>)> {
+        self.bounds
+    }
+
+    /// Sets the range and finds the lines within it.
+    /// Missing bounds leave that side of the range open.
+    pub fn set_range(
+        &mut self,
+        start: Option<Timestamp>,
+        end: Option<Timestamp>,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        self.bounds = Some((start, end));
+        self.error = None;
+        self.find_lines(log_reader, timestamp_order);
+    }
+
+    /// Sets the range to the given times, e.g. picked on the timeline, and shows them
+    /// in the range's text boxes.
+    pub fn select_range(
+        &mut self,
+        start: Timestamp,
+        end: Timestamp,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        self.editable_start = format_timestamp(start);
+        self.editable_end = format_timestamp(end);
+        self.set_range(Some(start), Some(end), log_reader, timestamp_order);
+    }
+
+    pub fn clear(&mut self) {
+        self.bounds = None;
+        self.lines = None;
+        self.scan_job = None;
+        self.error = None;
+        self.generation = next_lines_generation();
+    }
+
+    /// Keeps the lines up to date as the file is reloaded. Called every frame.
+    pub fn update(
+        &mut self,
+        ui: &Ui,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        if self.bounds.is_none() {
+            return;
+        }
+
+        let found_out_of_order = matches!(self.lines, Some(RangeLines::Contiguous(_)))
+            && timestamp_order.lock().out_of_order_lines() > 0;
+        if found_out_of_order || log_reader.load_time_point() != self.load_time {
+            self.find_lines(log_reader, timestamp_order);
+        }
+
+        let Some(scan_job) = &mut self.scan_job else {
+            return;
+        };
+        if let Some(RangeLines::Scanned(ref mut lines)) = self.lines {
+            scan_job.poll(Arc::make_mut(lines));
+        }
+        if !scan_job.is_finished() {
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+            return;
+        }
+        if let Some(error) = scan_job.error() {
+            error!("Failed to scan for time range: {:?}", error);
+            self.error = Some(error.to_string());
+        }
+        self.scan_job = None;
+    }
+
+    /// Locates the range by binary search if the file is ordered by time,
+    /// and by scanning every line otherwise.
+    fn find_lines(
+        &mut self,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        let Some((start, end)) = self.bounds else {
+            return;
+        };
+        self.load_time = log_reader.load_time_point();
+        self.generation = next_lines_generation();
+        self.scan_job = None;
+
+        // Until the order check completes, the file is assumed to be ordered.
+        if timestamp_order.lock().out_of_order_lines() == 0 {
+            let line_count = log_reader.line_count();
+            let first_line = match start {
+                Some(start) => partition_lines(log_reader, 0..line_count, |t| t < start),
+                None => 0,
+            };
+            let end_line = match end {
+                Some(end) => partition_lines(log_reader, first_line..line_count, |t| t <= end),
+                None => line_count,
+            };
+            self.lines = Some(RangeLines::Contiguous(first_line..end_line));
+        } else {
+            self.lines = Some(RangeLines::Scanned(Default::default()));
+            self.scan_job = Some(SearchJob::start_line_filter(
+                log_reader.line_index(),
+                0,
+                move |_, line| {
+                    let Some(timestamp) = LogFileReader::parse_logline(line).and_then(|e| e.time())
+                    else {
+                        return false;
+                    };
+                    start.map_or(true, |start| timestamp >= start)
+                        && end.map_or(true, |end| timestamp <= end)
+                },
+            ));
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("🕑 From:");
+            let start_response = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.editable_start)
+                        .hint_text("start of log")
+                        .desired_width(180.0),
+                )
+                .on_hover_text(BOUND_HELP);
+            ui.label("To:");
+            let end_response = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.editable_end)
+                        .hint_text("end of log")
+                        .desired_width(180.0),
+                )
+                .on_hover_text(BOUND_HELP);
+
+            let enter_pressed = (start_response.lost_focus() || end_response.lost_focus())
+                && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Apply").clicked() || enter_pressed {
+                self.apply_text(log_reader, timestamp_order);
+            }
+            if self.is_active() && ui.button("Clear").clicked() {
+                self.editable_start.clear();
+                self.editable_end.clear();
+                self.clear();
+            }
+
+            if let Some(ref error) = self.error {
+                ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+            } else if let Some(ref lines) = self.lines {
+                if self.scan_job.is_some() {
+                    ui.spinner();
+                    ui.label("Scanning out-of-order timestamps...");
+                }
+                let (start, end) = self.bounds.unwrap_or_default();
+                ui.label(format!("{} lines in range", lines.len()))
+                    .on_hover_text(format!(
+                        "{} to {}",
+                        start.map_or("start of log".into(), format_timestamp),
+                        end.map_or("end of log".into(), format_timestamp)
+                    ));
+            }
+        });
+    }
+
+    fn apply_text(
+        &mut self,
+        log_reader: &mut LogFileReader,
+        timestamp_order: &BackgroundAnalysis<TimestampOrder>,
+    ) {
+        if self.editable_start.trim().is_empty() && self.editable_end.trim().is_empty() {
+            self.clear();
+            return;
+        }
+
+        let (first, last) = timestamp_order.lock().bounds();
+        let bounds = resolve_bound(&self.editable_start, first, last, first).and_then(|start| {
+            let end = resolve_bound(&self.editable_end, first, last, start.or(first))?;
+            // A time of day before the start is taken to be on the next day.
+            let end = match (start, end) {
+                (Some(start), Some(end))
+                    if end < start && parse_time_of_day(&self.editable_end).is_some() =>
+                {
+                    Some(end + DAY_MS)
+                }
+                _ => end,
+            };
+            Ok((start, end))
+        });
+
+        match bounds {
+            Ok((start, end)) => self.set_range(start, end, log_reader, timestamp_order),
+            Err(error) => self.error = Some(error),
+        }
+    }
+}
+
+const BOUND_HELP: &str = "A timestamp (2023-05-31T14:02:00Z), a time of day on the first \
+entry's date (14:02), a duration before the last entry (-15m), or a duration after the \
+first entry (+5m). For \"To\", +5m is relative to \"From\".";
+
+/// Parses a time typed by the user. Empty text leaves the bound open.
+pub fn resolve_bound(
+    text: &str,
+    first: Option<Timestamp>,
+    last: Option<Timestamp>,
+    relative_base: Option<Timestamp>,
+) -> Result<Option<Timestamp>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = || format!("Invalid time: {}", text);
+    let no_entries = || "No timestamped entries in the log".to_string();
+
+    if let Some(duration) = text.strip_prefix('-') {
+        let duration = parse_duration(duration).ok_or_else(invalid)?;
+        let last = last.ok_or_else(no_entries)?;
+        return last.checked_sub(duration).map(Some).ok_or_else(invalid);
+    }
+    if let Some(duration) = text.strip_prefix('+') {
+        let duration = parse_duration(duration).ok_or_else(invalid)?;
+        let relative_base = relative_base.ok_or_else(no_entries)?;
+        return relative_base
+            .checked_add(duration)
+            .map(Some)
+            .ok_or_else(invalid);
+    }
+    if let Some(timestamp) = parse_timestamp(text) {
+        return Ok(Some(timestamp));
+    }
+    if let Some(time_of_day) = parse_time_of_day(text) {
+        let first = first.ok_or_else(no_entries)?;
+        return Ok(Some(first - first.rem_euclid(DAY_MS) + time_of_day));
+    }
+    Err(invalid())
+}
+
+/// Finds the first line in `lines` whose timestamp doesn't satisfy `is_before`,
+/// assuming timestamps are in ascending order. Lines without a timestamp are
+/// grouped with the next line that has one.
+pub fn partition_lines(
+    log_reader: &mut LogFileReader,
+    lines: Range<LineNumber>,
+    is_before: impl Fn(Timestamp) -> bool,
+) -> LineNumber {
+    let (mut low, mut high) = (lines.start, lines.end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let next_timestamp = (mid..high).find_map(|line_num| {
+            let timestamp = log_reader.read_entry(line_num)?.time()?;
+            Some((line_num, timestamp))
+        });
+        match next_timestamp {
+            Some((line_num, timestamp)) if is_before(timestamp) => low = line_num + 1,
+            _ => high = mid,
+        }
+    }
+    low
+}
@@ -0,0 +1,258 @@
+use std::time::SystemTime;
+
+use egui::{Align2, FontId, Rect, Stroke, Ui};
+
+use super::{
+    level_filter::SourceFingerprint,
+    log_entries_table::TableLines,
+    log_file_reader::LogFileReader,
+    timestamp::{format_timestamp, Timestamp, DAY_MS, HOUR_MS, MINUTE_MS},
+};
+
+/// Number of rows whose timestamps are read to map rows to times.
+const SAMPLE_COUNT: usize = 200;
+
+/// Rows read past a sampled row looking for one with a timestamp.
+const SAMPLE_SEARCH_ROWS: usize = 16;
+
+/// Intervals between ticks to choose from, shortest first.
+const TICK_INTERVALS: [i64; 11] = [
+    MINUTE_MS,
+    5 * MINUTE_MS,
+    10 * MINUTE_MS,
+    15 * MINUTE_MS,
+    30 * MINUTE_MS,
+    HOUR_MS,
+    3 * HOUR_MS,
+    6 * HOUR_MS,
+    12 * HOUR_MS,
+    DAY_MS,
+    7 * DAY_MS,
+];
+
+/// Least vertical space between tick labels.
+const TICK_SPACING: f32 = 24.0;
+
+/// Labels the vertical scroll bar of a log table with times, using timestamps read
+/// from a sample of the rows rather than from every line.
+#[derive(Default)]
+pub struct TimeScrollBar {
+    /// Rows with their timestamps, in ascending row order.
+    samples: Vec<(usize, Timestamp)>,
+    /// Time covered by the samples, leaving out stretches where time goes backwards.
+    forward_span: i64,
+    sampled_for: Option<(SourceFingerprint, usize, Option<SystemTime>)>,
+    /// Ticks chosen for the space last drawn in, cleared when the rows are sampled again.
+    ticks: Option<Ticks>,
+}
+
+/// Ticks at multiples of an interval, as fractional rows with the time crossed.
+struct Ticks {
+    /// Most ticks that fit in the space they were chosen for.
+    max_ticks: usize,
+    interval: i64,
+    boundaries: Vec<(f32, Timestamp)>,
+}
+
+impl TimeScrollBar {
+    /// Samples the rows again if the table's lines have changed.
+    pub fn update(&mut self, log_reader: &mut LogFileReader, lines: &TableLines<'_>) {
+        let key = (
+            SourceFingerprint::of(lines),
+            lines.len(),
+            log_reader.load_time_point(),
+        );
+        if self.sampled_for.as_ref() == Some(&key) {
+            return;
+        }
+        self.sampled_for = Some(key);
+
+        self.samples.clear();
+        self.ticks = None;
+        let row_count = lines.len();
+        if row_count == 0 {
+            return;
+        }
+        let sample_count = SAMPLE_COUNT.min(row_count);
+        for sample in 0..sample_count {
+            let row = sample * (row_count - 1) / (sample_count - 1).max(1);
+            if self
+                .samples
+                .last()
+                .is_some_and(|(last_row, _)| *last_row >= row)
+            {
+                continue;
+            }
+            let search_end = (row + SAMPLE_SEARCH_ROWS).min(row_count);
+            let timestamped_row = (row..search_end).find_map(|row| {
+                let timestamp = log_reader.read_entry(lines.get(row))?.time()?;
+                Some((row, timestamp))
+            });
+            if let Some(timestamped_row) = timestamped_row {
+                self.samples.push(timestamped_row);
+            }
+        }
+        self.forward_span = self
+            .samples
+            .windows(2)
+            .map(|pair| (pair[1].1 - pair[0].1).max(0))
+            .sum();
+    }
+
+    /// Estimated time of the given fractional row, interpolated between samples.
+    fn time_at_row(&self, row: f32) -> Option<Timestamp> {
+        let next = self
+            .samples
+            .partition_point(|(sample_row, _)| (*sample_row as f32) < row);
+        match (
+            self.samples.get(next.wrapping_sub(1)),
+            self.samples.get(next),
+        ) {
+            (Some(&(row0, time0)), Some(&(row1, time1))) => {
+                let fraction = (row - row0 as f32) / (row1 - row0) as f32;
+                Some(time0 + ((time1 - time0) as f32 * fraction) as i64)
+            }
+            (Some(&(_, time)), None) | (None, Some(&(_, time))) => Some(time),
+            (None, None) => None,
+        }
+    }
+
+    /// Fractional rows where the time crosses a multiple of `interval`, with the
+    /// time crossed. Stretches where time goes backwards are skipped.
+    fn boundaries(&self, interval: i64) -> Vec<(f32, Timestamp)> {
+        let mut boundaries = vec![];
+        for pair in self.samples.windows(2) {
+            let ((row0, time0), (row1, time1)) = (pair[0], pair[1]);
+            if time1 <= time0 {
+                continue;
+            }
+            let mut boundary = (time0.div_euclid(interval) + 1) * interval;
+            while boundary <= time1 {
+                let fraction = (boundary - time0) as f32 / (time1 - time0) as f32;
+                boundaries.push((row0 as f32 + fraction * (row1 - row0) as f32, boundary));
+                boundary += interval;
+            }
+        }
+        boundaries
+    }
+
+    /// Ticks at the shortest interval giving at most `max_ticks`. Intervals are only tried
+    /// once the span of the samples suggests few enough ticks.
+    fn choose_ticks(&self, max_ticks: usize) -> Ticks {
+        let (interval, boundaries) = TICK_INTERVALS
+            .into_iter()
+            .filter(|interval| (self.forward_span / interval) as usize <= max_ticks)
+            .map(|interval| (interval, self.boundaries(interval)))
+            .find(|(_, boundaries)| boundaries.len() <= max_ticks)
+            .unwrap_or_default();
+        Ticks {
+            max_ticks,
+            interval,
+            boundaries,
+        }
+    }
+
+    /// Draws ticks beside the scroll bar of a table body showing `row_count` rows, with
+    /// labels while the pointer is over the scroll bar and the time under the pointer
+    /// while it's dragged.
+    pub fn ui(&mut self, ui: &Ui, body_rect: Rect, row_count: usize) {
+        if self.samples.len() < 2 || row_count == 0 {
+            return;
+        }
+        let scroll_style = &ui.spacing().scroll;
+        let bar_width =
+            scroll_style.bar_width + scroll_style.bar_inner_margin + scroll_style.bar_outer_margin;
+        let bar_rect = Rect::from_x_y_ranges(
+            (body_rect.right() - bar_width)..=body_rect.right(),
+            body_rect.y_range(),
+        );
+
+        let y_of = |row: f32| body_rect.top() + row / row_count as f32 * body_rect.height();
+        let row_at = |y: f32| {
+            ((y - body_rect.top()) / body_rect.height() * row_count as f32)
+                .clamp(0.0, (row_count - 1) as f32)
+        };
+
+        let max_ticks = (body_rect.height() / TICK_SPACING).max(1.0) as usize;
+        if self.ticks.as_ref().map(|ticks| ticks.max_ticks) != Some(max_ticks) {
+            self.ticks = Some(self.choose_ticks(max_ticks));
+        }
+        let Some(Ticks {
+            interval,
+            ref boundaries,
+            ..
+        }) = self.ticks
+        else {
+            return;
+        };
+
+        let dragged_y = ui.input(|i| {
+            let is_dragging = i.pointer.primary_down()
+                && i.pointer
+                    .press_origin()
+                    .is_some_and(|p| bar_rect.contains(p));
+            i.pointer
+                .interact_pos()
+                .filter(|_| is_dragging)
+                .map(|p| p.y)
+        });
+        let show_labels = dragged_y.is_some() || ui.rect_contains_pointer(bar_rect);
+
+        let painter = ui.painter_at(body_rect);
+        let tick_stroke = Stroke::new(1.0, ui.visuals().weak_text_color());
+        let label_font = FontId::proportional(10.0);
+        for &(row, time) in boundaries {
+            let y = y_of(row);
+            painter.hline((bar_rect.left() - 6.0)..=bar_rect.left(), y, tick_stroke);
+            if show_labels {
+                let text = if interval >= DAY_MS || time.rem_euclid(DAY_MS) == 0 {
+                    format_timestamp(time)[..10].to_string()
+                } else {
+                    format_timestamp(time)[11..16].to_string()
+                };
+                Self::paint_label(
+                    ui,
+                    &painter,
+                    bar_rect.left() - 8.0,
+                    y,
+                    text,
+                    label_font.clone(),
+                );
+            }
+        }
+
+        if let Some(y) = dragged_y {
+            if let Some(time) = self.time_at_row(row_at(y)) {
+                Self::paint_label(
+                    ui,
+                    &painter,
+                    bar_rect.left() - 8.0,
+                    y.clamp(body_rect.top(), body_rect.bottom()),
+                    format_timestamp(time),
+                    FontId::proportional(13.0),
+                );
+            }
+        }
+    }
+
+    /// Paints text right-aligned to `right`, over a background so it stays readable
+    /// on top of the table's rows.
+    fn paint_label(
+        ui: &Ui,
+        painter: &egui::Painter,
+        right: f32,
+        y: f32,
+        text: String,
+        font: FontId,
+    ) {
+        let galley = painter.layout_no_wrap(text, font, ui.visuals().strong_text_color());
+        let rect = Align2::RIGHT_CENTER.anchor_size(egui::pos2(right, y), galley.size());
+        painter.rect(
+            rect.expand(2.0),
+            2.0,
+            ui.visuals().window_fill,
+            ui.visuals().window_stroke,
+        );
+        painter.galley(rect.min, galley, ui.visuals().strong_text_color());
+    }
+}
@@ -0,0 +1,223 @@
+//! Parsing and formatting of ISO-8601 log entry timestamps.
+//!
+//! Timestamps are converted to milliseconds since the Unix epoch (UTC) so they
+//! can be compared and subtracted regardless of the offset they were written with.
+
+/// Milliseconds since 1970-01-01T00:00:00Z.
+pub type Timestamp = i64;
+
+pub const SECOND_MS: i64 = 1000;
+pub const MINUTE_MS: i64 = 60 * SECOND_MS;
+pub const HOUR_MS: i64 = 60 * MINUTE_MS;
+pub const DAY_MS: i64 = 24 * HOUR_MS;
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            bytes: text.trim().as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matched = self.peek() == Some(byte);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    /// Reads exactly `digits` decimal digits.
+    fn number(&mut self, digits: usize) -> Option<i64> {
+        let text = self.bytes.get(self.pos..self.pos + digits)?;
+        if !text.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.pos += digits;
+        Some(text.iter().fold(0, |n, d| n * 10 + (d - b'0') as i64))
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+}
+
+/// Parses a timestamp such as `2023-05-31T19:51:05.947Z`, `2023-05-31 19:51:05+02:00`
+/// or `2023-05-31`. Times without an offset are taken as UTC.
+pub fn parse_timestamp(text: &str) -> Option<Timestamp> {
+    let mut cursor = Cursor::new(text);
+
+    let year = cursor.number(4)?;
+    cursor.eat(b'-').then_some(())?;
+    let month = cursor.number(2)?;
+    cursor.eat(b'-').then_some(())?;
+    let day = cursor.number(2)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut timestamp = days_from_civil(year, month, day) * DAY_MS;
+    if cursor.eat(b'T') || cursor.eat(b't') || cursor.eat(b' ') {
+        timestamp += parse_time(&mut cursor)?;
+        timestamp -= parse_offset(&mut cursor)?;
+    }
+
+    cursor.is_at_end().then_some(timestamp)
+}
+
+/// Parses a time of day such as `14:02` or `14:02:30.5`, returning milliseconds since midnight.
+pub fn parse_time_of_day(text: &str) -> Option<i64> {
+    let mut cursor = Cursor::new(text);
+    let time = parse_time(&mut cursor)?;
+    cursor.is_at_end().then_some(time)
+}
+
+fn parse_time(cursor: &mut Cursor<'_>) -> Option<i64> {
+    let hours = cursor.number(2)?;
+    cursor.eat(b':').then_some(())?;
+    let minutes = cursor.number(2)?;
+    let mut seconds = 0;
+    let mut millis = 0;
+    if cursor.eat(b':') {
+        seconds = cursor.number(2)?;
+        if cursor.eat(b'.') || cursor.eat(b',') {
+            let mut scale = 100;
+            while let Some(digit) = cursor.number(1) {
+                millis += digit * scale;
+                scale /= 10;
+            }
+        }
+    }
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    Some(hours * HOUR_MS + minutes * MINUTE_MS + seconds * SECOND_MS + millis)
+}
+
+/// Parses a UTC offset (`Z`, `+02:00`, `-0530`), returning it in milliseconds.
+fn parse_offset(cursor: &mut Cursor<'_>) -> Option<i64> {
+    if cursor.eat(b'Z') || cursor.eat(b'z') || cursor.is_at_end() {
+        return Some(0);
+    }
+    let sign = if cursor.eat(b'+') {
+        1
+    } else if cursor.eat(b'-') {
+        -1
+    } else {
+        return None;
+    };
+    let hours = cursor.number(2)?;
+    cursor.eat(b':');
+    let minutes = cursor.number(2).unwrap_or(0);
+    Some(sign * (hours * HOUR_MS + minutes * MINUTE_MS))
+}
+
+/// Parses a duration such as `90s`, `5m`, `1h30m`, `2d` or `250ms`.
+/// Returns None if it's too long to be represented.
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let mut rest = text;
+    let mut total: i64 = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_ms = match rest[..unit].trim() {
+            "ms" => 1,
+            "s" => SECOND_MS,
+            "m" | "min" => MINUTE_MS,
+            "h" => HOUR_MS,
+            "d" => DAY_MS,
+            _ => return None,
+        };
+        total = value
+            .checked_mul(unit_ms)
+            .and_then(|value_ms| total.checked_add(value_ms))?;
+        rest = &rest[unit..];
+    }
+    (!text.is_empty()).then_some(total)
+}
+
+/// Formats a duration in the syntax `parse_duration` accepts, e.g. `1d2h5m` or `3s250ms`.
+pub fn format_duration(duration: i64) -> String {
+    let units = [
+        (DAY_MS, "d"),
+        (HOUR_MS, "h"),
+        (MINUTE_MS, "m"),
+        (SECOND_MS, "s"),
+        (1, "ms"),
+    ];
+    let mut rest = duration.abs();
+    let mut text = if duration < 0 { "-".to_string() } else { String::new() };
+    for (unit_ms, unit) in units {
+        if rest >= unit_ms {
+            text += &format!("{}{}", rest / unit_ms, unit);
+            rest %= unit_ms;
+        }
+    }
+    if text.is_empty() {
+        text = "0s".to_string();
+    }
+    text
+}
+
+/// Formats a timestamp as `2023-05-31T19:51:05.947Z`.
+pub fn format_timestamp(timestamp: Timestamp) -> String {
+    let days = timestamp.div_euclid(DAY_MS);
+    let time = timestamp.rem_euclid(DAY_MS);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / HOUR_MS,
+        time % HOUR_MS / MINUTE_MS,
+        time % MINUTE_MS / SECOND_MS,
+        time % SECOND_MS
+    )
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian calendar date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}